@@ -0,0 +1,210 @@
+use crate::prelude::*;
+use chrono::{prelude::*, Duration};
+use std::fs;
+use std::path::Path;
+use std::sync::Once;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DayOffKind {
+    Holiday,
+    Leave,
+}
+
+// A day nobody works, `label` is what its timelog row shows
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayOff {
+    pub date: NaiveDate,
+    pub kind: DayOffKind,
+    pub label: String,
+}
+
+// Weekend days plus holidays and leave, built by `Config::work_calendar`
+#[derive(Debug, Clone, Default)]
+pub struct WorkCalendar {
+    pub weekend: Vec<Weekday>,
+    pub days_off: Vec<DayOff>,
+}
+
+impl WorkCalendar {
+    pub fn is_weekend(&self, date: NaiveDate) -> bool {
+        self.weekend.contains(&date.weekday())
+    }
+
+    // Leave wins over a holiday on the same day
+    pub fn day_off(&self, date: NaiveDate) -> Option<&DayOff> {
+        let mut days_off = self.days_off.iter().filter(|day_off| day_off.date == date);
+        let first = days_off.next()?;
+        Some(days_off.find(|day_off| day_off.kind == DayOffKind::Leave).unwrap_or(first))
+    }
+
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        !self.is_weekend(date) && self.day_off(date).is_none()
+    }
+
+    // The last working day before `date`, e.g. Friday for a Monday stand-up
+    pub fn previous_working_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut day = date - Duration::days(1);
+        while !self.is_working_day(day) {
+            day -= Duration::days(1);
+        }
+        day
+    }
+}
+
+// Unix timestamp of local midnight at the start of `date`
+pub fn start_of_day(date: NaiveDate) -> i64 {
+    date.and_time(NaiveTime::MIN)
+        .and_local_timezone(Local)
+        .earliest()
+        .map_or_else(|| date.and_time(NaiveTime::MIN).and_utc().timestamp(), |start| start.timestamp())
+}
+
+// `HH:MM` is the next time the clock shows it, `YYYY-MM-DD HH:MM` has to be in the future
+pub fn parse_post_at(value: &str, now: DateTime<Local>) -> Result<DateTime<Local>, Error> {
+    let value = value.trim();
+    let invalid = || Error::Validation(format!(
+        "`{}` is not a time, use HH:MM or YYYY-MM-DD HH:MM",
+        value
+    ));
+    let at = match NaiveTime::parse_from_str(value, "%H:%M") {
+        Ok(time) => {
+            let today = now.date_naive().and_time(time);
+            if today > now.naive_local() { today } else { today + Duration::days(1) }
+        }
+        Err(_) => NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+            .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+            .map_err(|_| invalid())?,
+    };
+    let at = at.and_local_timezone(Local).earliest().ok_or_else(invalid)?;
+    if at <= now {
+        return Err(Error::Validation(format!("{} is in the past", at.format("%Y-%m-%d %H:%M"))));
+    }
+    Ok(at)
+}
+
+// Comma separated YYYY-MM-DD dates as found in `standup.holidays`
+pub fn parse_holidays(holidays: &str) -> Result<Vec<NaiveDate>, Error> {
+    holidays
+        .split(',')
+        .map(str::trim)
+        .filter(|date| !date.is_empty())
+        .map(|date| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| Error::config(
+                "standup.holidays",
+                format!("`{}` is not a date, use comma separated YYYY-MM-DD dates", date)
+            ))
+        })
+        .collect()
+}
+
+// Comma separated weekday names as found in `standup.weekend`, e.g. "Fri, Sat"
+pub fn parse_weekend(weekend: &str) -> Result<Vec<Weekday>, Error> {
+    let days = weekend
+        .split(',')
+        .map(str::trim)
+        .filter(|day| !day.is_empty())
+        .map(|day| {
+            day.parse::<Weekday>().map_err(|_| Error::config(
+                "standup.weekend",
+                format!("`{}` is not a weekday, use comma separated names like Sat, Sun", day)
+            ))
+        })
+        .collect::<Result<Vec<Weekday>, Error>>()?;
+    if days.len() >= 7 {
+        return Err(Error::config("standup.weekend", "at least one day of the week has to be a working day"));
+    }
+    Ok(days)
+}
+
+#[derive(Debug, Deserialize)]
+struct YamlHoliday {
+    date: String,
+    label: Option<String>,
+}
+
+// Holidays of an .ics calendar, or of a YAML list of `date` (YYYY-MM-DD) and `label`
+pub fn read_holiday_calendar(path: &str) -> Result<Vec<DayOff>, Error> {
+    let contents = fs::read_to_string(path).map_err(|e| Error::local_file(path, e))?;
+    let is_ics = Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ics"));
+    if is_ics {
+        let (days, recurring) = parse_ics(&contents).map_err(|e| Error::local_file(path, e))?;
+        // the calendar is read by every command that needs it, once is enough to tell
+        static WARNED: Once = Once::new();
+        if !recurring.is_empty() {
+            WARNED.call_once(|| {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "{}: recurring events (RRULE) only count on their first day, list the other dates as events: {}",
+                        path,
+                        recurring.join(", ")
+                    )
+                    .yellow()
+                )
+            });
+        }
+        return Ok(days);
+    }
+    let holidays: Vec<YamlHoliday> = serde_yaml::from_str(&contents)
+        .map_err(|e| Error::local_file(path, format!("{}, expected a list of date and label", e)))?;
+    holidays
+        .into_iter()
+        .map(|holiday| {
+            let date = NaiveDate::parse_from_str(holiday.date.trim(), "%Y-%m-%d")
+                .map_err(|_| Error::local_file(path, format!("`{}` is not a YYYY-MM-DD date", holiday.date)))?;
+            Ok(holiday_on(date, holiday.label))
+        })
+        .collect()
+}
+
+fn holiday_on(date: NaiveDate, label: Option<String>) -> DayOff {
+    DayOff {
+        date,
+        kind: DayOffKind::Holiday,
+        label: label.filter(|label| !label.trim().is_empty()).unwrap_or("Holiday".to_string()),
+    }
+}
+
+// Every day covered by a VEVENT, labelled with its SUMMARY, and the summaries of the events
+// with an RRULE, which isn't expanded. DTEND is exclusive like all-day events have it
+fn parse_ics(contents: &str) -> Result<(Vec<DayOff>, Vec<String>), String> {
+    // long lines are folded onto the next ones starting with a space or a tab
+    let unfolded = contents.replace("\r\n", "\n").replace("\n ", "").replace("\n\t", "");
+    let (mut days, mut recurring) = (Vec::new(), Vec::new());
+    let (mut start, mut end, mut summary, mut rrule) = (None, None, None, false);
+    for line in unfolded.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let property = name.split(';').next().unwrap_or_default().to_uppercase();
+        match (property.as_str(), value.trim()) {
+            ("BEGIN", "VEVENT") => (start, end, summary, rrule) = (None, None, None, false),
+            ("DTSTART", value) => start = Some(ics_date(value)?),
+            ("DTEND", value) => end = Some(ics_date(value)?),
+            ("SUMMARY", value) => summary = Some(value.replace("\\,", ",").replace("\\;", ";")),
+            ("RRULE", _) => rrule = true,
+            ("END", "VEVENT") => {
+                let start: NaiveDate = start.ok_or("an event has no DTSTART")?;
+                if rrule {
+                    recurring.push(summary.clone().unwrap_or_else(|| start.to_string()));
+                }
+                let end = end.filter(|end| *end > start).unwrap_or(start + Duration::days(1));
+                let mut date = start;
+                while date < end {
+                    days.push(holiday_on(date, summary.clone()));
+                    date += Duration::days(1);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((days, recurring))
+}
+
+// 20261225 or 20261225T090000Z, only the day matters
+fn ics_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.get(..8).unwrap_or(value), "%Y%m%d")
+        .map_err(|_| format!("`{}` is not an iCalendar date", value))
+}
@@ -1,11 +1,11 @@
-use anyhow::Error;
-
 use crate::prelude::*;
+use crate::commands::timelog;
+use std::path::PathBuf;
 use std::process::ExitCode;
 
 #[async_trait]
 pub trait RunCommand{
-    async fn run(self) -> Result<(), Error>;
+    async fn run(self, config: Config) -> Result<(), Error>;
 }
 
 #[derive(Parser)]
@@ -13,6 +13,18 @@ pub trait RunCommand{
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Named profile from the config file to apply
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Path of the config file [default: $XDG_CONFIG_HOME/automate-me/config.toml]
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Print what would be sent to Notion, Slack and the sheet instead of sending it
+    #[arg(long, global = true)]
+    pub dry_run: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -22,23 +34,60 @@ pub enum Commands  {
     /// generate stand up and post on slack and sheet based on flag provided
     GenerateStandUp(generate_stand_up::GenerateStandUp),
     /// add new tasks to notion task manager
-    AddTask(add_task::AddTask)
+    AddTask(add_task::AddTask),
+    /// show, validate or create the config file
+    Config(config::ConfigCommand),
+    /// sync and list the notion projects tasks can belong to
+    Projects(projects::Projects),
+    /// list tasks and move them through their statuses
+    Tasks(tasks::Tasks),
+    /// manage stand-ups scheduled on slack
+    Slack(slack::SlackCommand),
+    /// record leave and look after the timelog sheet
+    Timelog(timelog::TimelogCommand),
 }
 
 impl Cli {
     pub async fn run(self) -> ExitCode {
-        let output = match self.command {
-            Commands::GiveMeArticle(give_me_article) => give_me_article.run().await,
-            Commands::GenerateStandUp(generate_stand_up) => generate_stand_up.run().await,
-            Commands::AddTask(add_task) => add_task.run().await
+        let profile = self.profile.or_else(|| env::var("AUTOMATE_ME_PROFILE").ok());
+        let init_force = match &self.command {
+            Commands::Config(config_command) => config_command.init_force(),
+            _ => None,
+        };
+        let output = match init_force {
+            Some(force) => crate::config::path_or_default(self.config).and_then(|path| config::init(&path, force)),
+            None => match Config::load(self.config, profile) {
+                Ok(mut config) => {
+                    config.dry_run = self.dry_run;
+                    match self.command {
+                        Commands::GiveMeArticle(give_me_article) => give_me_article.run(config).await,
+                        Commands::GenerateStandUp(generate_stand_up) => generate_stand_up.run(config).await,
+                        Commands::AddTask(add_task) => add_task.run(config).await,
+                        Commands::Config(config_command) => config_command.run(config).await,
+                        Commands::Projects(projects) => projects.run(config).await,
+                        Commands::Tasks(tasks) => tasks.run(config).await,
+                        Commands::Slack(slack) => slack.run(config).await,
+                        Commands::Timelog(timelog) => timelog.run(config).await,
+                    }
+                }
+                Err(e) => Err(e),
+            },
         };
 
         match output {
             Ok(()) => ExitCode::SUCCESS,
             Err(e) => {
-                eprintln!("{}", e);
-                ExitCode::FAILURE
+                eprintln!("{}", e.to_string().red());
+                ExitCode::from(e.exit_code())
             }
         }
     }
 }
+
+// Printed in place of a side effect when --dry-run is set
+pub fn print_dry_run(action: &str, details: &str) {
+    println!("{} {}", "[dry run]".cyan(), action.yellow());
+    if !details.is_empty() {
+        println!("{}", details);
+    }
+}
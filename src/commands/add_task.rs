@@ -1,34 +1,250 @@
 use crate::prelude::*;
+use dialoguer::{theme::ColorfulTheme, Confirm, FuzzySelect, Input, Select};
+use futures::stream::{self, StreamExt};
+use stand_up::Status;
+use std::io::IsTerminal;
+
+// Notion allows an average of three requests per second
+const MAX_CONCURRENT_REQUESTS: usize = 3;
 
 #[derive(Debug, Args)]
 pub struct AddTask {
     /// Title of task
     #[arg(short, long)]
-    task: String,
+    task: Option<String>,
 
     /// Status of task
-    /// Possible values: "to do", "in progress", "done"
+    /// Possible values: "to do", "in progress", "done", "blocked" (the standup.blocked_status option)
     #[arg(short, long, default_value = "done")]
     status: String,
 
     /// Project of task
     #[arg(short, long)]
-    project: String,
+    project: Option<String>,
+
+    /// Prompt for tasks interactively, also used when --task or --project is missing
+    #[arg(short, long)]
+    interactive: bool,
+
+    /// Import tasks from a csv (task,status,project) or markdown checklist, "-" reads stdin
+    #[arg(short, long, conflicts_with_all = ["task", "interactive"])]
+    from_file: Option<String>,
+}
+
+struct NewTask {
+    line: usize,
+    title: String,
+    status: Status,
+    project: project_cache::Project,
 }
 
 #[async_trait]
 impl RunCommand for AddTask {
-    async fn run(self) -> Result<(), Error> {
-        let database_id = env::var("NOTION_TASK_DATABASE_ID").unwrap();
-        let api = NotionApi::new("pages");
-
-        api.add_task(
-            self.task.clone(),
-            self.status,
-            database_id,
-            self.project
-        ).await?;
-        println!("{}", self.task);
+    async fn run(self, config: Config) -> Result<(), Error> {
+        let database_id = config.require("notion.task_database_id")?;
+        let projects = project_cache::ProjectCache::load(&config.project_cache()?)?;
+        let blocked_status = config.get("standup.blocked_status");
+
+        if let Some(path) = &self.from_file {
+            return self.import(path, &config, database_id, &projects, blocked_status).await;
+        }
+
+        let tasks = match (self.interactive, &self.task, &self.project) {
+            (false, Some(task), Some(project)) => vec![NewTask {
+                line: 0,
+                title: task.clone(),
+                status: Status::parse(&self.status, blocked_status)?,
+                project: projects.resolve(project)?.clone(),
+            }],
+            _ => {
+                if !std::io::stdin().is_terminal() {
+                    return Err(Error::Validation(
+                        "--task and --project are required when not running in a terminal".to_string()
+                    ));
+                }
+                let tasks = self.prompt_tasks(&projects, blocked_status)?;
+                if tasks.is_empty() {
+                    return Ok(());
+                }
+                tasks
+            }
+        };
+
+        let api = NotionApi::new(&config, "pages")?;
+        for task in tasks {
+            let page = api.add_task(
+                task.title.clone(),
+                task.status,
+                database_id.clone(),
+                task.project.id
+            ).await?;
+            println!("{}    {}", task.title.green(), page.url.blue());
+        }
         Ok(())
     }
 }
+
+impl AddTask {
+    // Send every row of the file, a few at a time, and report what failed
+    async fn import(
+        &self,
+        path: &str,
+        config: &Config,
+        database_id: String,
+        projects: &project_cache::ProjectCache,
+        blocked_status: Option<&str>,
+    ) -> Result<(), Error> {
+        let rows = task_file::read_task_file(path, &self.status)?;
+        println!("{}", format!("Importing {} tasks", rows.len()).yellow());
+        let mut failed: Vec<(usize, String, Error)> = Vec::new();
+        let mut tasks = Vec::new();
+        for row in rows {
+            let row = match row {
+                Ok(row) => row,
+                Err(failed_row) => {
+                    failed.push((failed_row.line, failed_row.title, failed_row.error));
+                    continue;
+                }
+            };
+            let project = match row.project.as_ref().or(self.project.as_ref()) {
+                Some(project) => projects.resolve(project).cloned(),
+                None => Err(Error::Validation("No project, add a heading/column or pass --project".to_string())),
+            };
+            match Status::parse(&row.status, blocked_status).and_then(|status| project.map(|project| (status, project))) {
+                Ok((status, project)) => tasks.push(NewTask {
+                    line: row.line,
+                    title: row.title,
+                    status,
+                    project,
+                }),
+                Err(e) => failed.push((row.line, row.title, e)),
+            }
+        }
+
+        let api = NotionApi::new(config, "pages")?;
+        let mut results: Vec<(NewTask, Result<response::PageResponse, Error>)> = stream::iter(tasks)
+            .map(|task| {
+                let api = &api;
+                let database_id = database_id.clone();
+                async move {
+                    let result = api.add_task(
+                        task.title.clone(),
+                        task.status.clone(),
+                        database_id,
+                        task.project.id.clone()
+                    ).await;
+                    (task, result)
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+            .collect()
+            .await;
+        results.sort_by_key(|(task, _)| task.line);
+
+        let mut created = 0;
+        for (task, result) in results {
+            match result {
+                Ok(page) => {
+                    created += 1;
+                    println!("{}    {}", task.title.green(), page.url.blue());
+                }
+                Err(e) => failed.push((task.line, task.title, e)),
+            }
+        }
+
+        println!("{}", format!("Created {} tasks, {} failed", created, failed.len()).yellow());
+        failed.sort_by_key(|(line, _, _)| *line);
+        for (line, title, e) in failed.iter() {
+            println!(" • line {} {}: {}", line, title, e.to_string().red());
+        }
+        match failed.into_iter().next() {
+            Some((_, _, e)) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    // Collect as many tasks as the user wants and confirm before anything is sent
+    fn prompt_tasks(
+        &self,
+        projects: &project_cache::ProjectCache,
+        blocked_status: Option<&str>,
+    ) -> Result<Vec<NewTask>, Error> {
+        if projects.projects.is_empty() {
+            return Err(Error::Validation(
+                "No projects to choose from, run `automate-me projects sync`".to_string()
+            ));
+        }
+        let theme = ColorfulTheme::default();
+        let project_names: Vec<&str> = projects.projects.iter().map(|project| project.name.as_str()).collect();
+        let statuses = Status::all(blocked_status);
+        let status_names: Vec<String> = statuses.iter().map(|status| status.to_string()).collect();
+        let mut status_index = statuses
+            .iter()
+            .position(|status| Status::parse(&self.status, blocked_status).ok().as_ref() == Some(status))
+            .unwrap_or(0);
+        let mut project_index = match &self.project {
+            Some(project) => {
+                let id = &projects.resolve(project)?.id;
+                projects.projects.iter().position(|project| &project.id == id).unwrap_or(0)
+            }
+            None => 0,
+        };
+        let mut tasks = Vec::new();
+
+        loop {
+            let mut title = Input::<String>::with_theme(&theme).with_prompt("Task");
+            if let (true, Some(task)) = (tasks.is_empty(), &self.task) {
+                title = title.with_initial_text(task);
+            }
+            let title = title.interact_text().map_err(prompt_error)?;
+
+            status_index = Select::with_theme(&theme)
+                .with_prompt("Status")
+                .items(&status_names)
+                .default(status_index)
+                .interact()
+                .map_err(prompt_error)?;
+
+            project_index = FuzzySelect::with_theme(&theme)
+                .with_prompt("Project (type to filter)")
+                .items(&project_names)
+                .default(project_index)
+                .interact()
+                .map_err(prompt_error)?;
+
+            tasks.push(NewTask {
+                line: tasks.len() + 1,
+                title,
+                status: statuses[status_index].clone(),
+                project: projects.projects[project_index].clone(),
+            });
+
+            let another = Confirm::with_theme(&theme)
+                .with_prompt("Add another task?")
+                .default(false)
+                .interact()
+                .map_err(prompt_error)?;
+            if !another {
+                break;
+            }
+        }
+
+        for task in tasks.iter() {
+            println!(" • {} [{}] {}", task.title, task.status, task.project.name.dimmed());
+        }
+        let send = Confirm::with_theme(&theme)
+            .with_prompt(format!("Send {} task(s) to Notion?", tasks.len()))
+            .default(true)
+            .interact()
+            .map_err(prompt_error)?;
+        if !send {
+            println!("{}", "Nothing sent".yellow());
+            tasks.clear();
+        }
+        Ok(tasks)
+    }
+}
+
+fn prompt_error(error: dialoguer::Error) -> Error {
+    Error::Validation(format!("Interactive prompt failed: {}", error))
+}
@@ -0,0 +1,100 @@
+use crate::prelude::*;
+use crate::config::{self, Source, SETTINGS};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Args)]
+pub struct ConfigCommand {
+    #[command(subcommand)]
+    action: ConfigAction,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigAction {
+    /// print every setting with where its value comes from
+    Show,
+    /// report missing or invalid settings without calling any API
+    Validate,
+    /// write a config file template to the config path
+    Init {
+        /// Overwrite the config file if it already exists
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+#[async_trait]
+impl RunCommand for ConfigCommand {
+    async fn run(self, config: Config) -> Result<(), Error> {
+        match self.action {
+            ConfigAction::Show => show(&config),
+            ConfigAction::Validate => validate(&config),
+            ConfigAction::Init { force } => init(&config.path, force),
+        }
+    }
+}
+
+impl ConfigCommand {
+    // `config init` runs without loading the config, so it can replace a file that doesn't parse
+    pub fn init_force(&self) -> Option<bool> {
+        match self.action {
+            ConfigAction::Init { force } => Some(force),
+            _ => None,
+        }
+    }
+}
+
+fn show(config: &Config) -> Result<(), Error> {
+    println!("{}    {}", "Config file".yellow(), config.path.display());
+    if let Some(profile) = &config.profile {
+        println!("{}    {}", "Profile".yellow(), profile);
+    }
+    for setting in SETTINGS {
+        let value = match config.get(setting.key) {
+            Some(_) if setting.secret => "********".to_string(),
+            Some(value) => value.to_string(),
+            None => "-".to_string(),
+        };
+        let source = match config.source(setting.key) {
+            Source::Env => format!("(env {})", setting.env),
+            Source::File => "(file)".to_string(),
+            Source::Unset => "(unset)".to_string(),
+        };
+        println!("{:<34} {} {}", setting.key, value, source.dimmed());
+    }
+    Ok(())
+}
+
+fn validate(config: &Config) -> Result<(), Error> {
+    let problems = config.problems();
+    for note in config.notes() {
+        println!(" • {}", note.yellow());
+    }
+    if problems.is_empty() {
+        println!("{}", "Config is valid".green());
+        return Ok(());
+    }
+    for problem in &problems {
+        println!(" • {}", problem.red());
+    }
+    Err(Error::config(
+        "config",
+        format!("{} problem(s) found in {}", problems.len(), config.path.display()),
+    ))
+}
+
+pub fn init(path: &Path, force: bool) -> Result<(), Error> {
+    let display_path = path.display().to_string();
+    if path.exists() && !force {
+        return Err(Error::config(
+            "config",
+            format!("{} already exists, pass --force to overwrite it", display_path),
+        ));
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| Error::local_file(&dir.display().to_string(), e))?;
+    }
+    fs::write(path, config::template()).map_err(|e| Error::local_file(&display_path, e))?;
+    println!("{}    {}", "Wrote config template to".green(), display_path.blue());
+    Ok(())
+}
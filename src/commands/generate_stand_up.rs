@@ -1,4 +1,9 @@
 use crate::prelude::*;
+use crate::calendar;
+use crate::notifier::{self, StandUp};
+use crate::sheet::timelog;
+use chrono::prelude::*;
+use futures::future::join_all;
 
 #[derive(Debug, Args)]
 pub struct GenerateStandUp {
@@ -6,6 +11,10 @@ pub struct GenerateStandUp {
     #[arg(short, long)]
     slack: bool,
 
+    /// Send the stand up to every target in notify.targets
+    #[arg(short, long)]
+    notify: bool,
+
     /// Flag for updating timelog on google sheet
     #[arg(short, long)]
     timelog: bool,
@@ -17,40 +26,210 @@ pub struct GenerateStandUp {
     /// data to fill out in timelog Hours header
     #[arg(short = 'w', long, default_value = "8")]
     hours: String,
+
+    /// How to treat a timelog row that is already filled
+    #[arg(long, value_enum, default_value_t = timelog::TimelogMode::Append)]
+    timelog_mode: timelog::TimelogMode,
+
+    /// ad-hoc blocker to add to the stand up, can be repeated
+    #[arg(short, long)]
+    blocker: Vec<String>,
+
+    /// Don't group Today and Tomorrow tasks under their project
+    #[arg(long)]
+    no_group: bool,
+
+    /// Date of the stand-up (YYYY-MM-DD), to regenerate a missed one [default: today]
+    #[arg(long)]
+    date: Option<NaiveDate>,
+
+    /// Include tasks edited on or after this date (YYYY-MM-DD) [default: the stand-up date]
+    #[arg(long, conflicts_with = "previous_working_day")]
+    since: Option<NaiveDate>,
+
+    /// Include tasks edited since the previous working day, skipping weekends and holidays
+    #[arg(long)]
+    previous_working_day: bool,
+
+    /// Post the stand-up as a reply in this Slack thread instead of looking for today's thread
+    #[arg(long, conflicts_with = "update")]
+    thread_ts: Option<String>,
+
+    /// Edit the stand-up already posted to Slack for this date instead of posting a new one
+    #[arg(long)]
+    update: bool,
+
+    /// Let Slack post the stand-up later, at HH:MM (next occurrence) or YYYY-MM-DD HH:MM
+    #[arg(long, conflicts_with = "update")]
+    at: Option<String>,
 }
 
 #[async_trait]
 impl RunCommand for GenerateStandUp {
-    async fn run(self) ->  Result<(), Error> {
+    async fn run(self, config: Config) ->  Result<(), Error> {
         println!("{}", "Generating stand up".yellow());
-        let database_id = env::var("NOTION_TASK_DATABASE_ID").unwrap();
+        let database_id = config.require("notion.task_database_id")?;
+        let template = template::load(&config)?;
+        let (since, date) = self.date_range(&config)?;
+        let notifiers = self.notifiers(&config)?;
         let api = NotionApi::new(
+            &config,
             &format!(
                 "databases/{}/query",
                 database_id
             )
-        );
-        let tasks = api.get_tasks().await?;
-        let stand_up = tasks.tasks_for_standup();
+        )?;
+        let tasks = api.get_tasks(since, date, config.get("standup.blocked_status")).await?;
+        let projects = project_names(&config, tasks.project_ids()).await?;
+        let grouped = !self.no_group;
+        let context = tasks.standup_context(date, &projects, &self.blocker, grouped);
+        let stand_up = template::render(&template, &context)?;
         println!("{}", stand_up.green());
-        if self.slack {
-            let slack_api = SlackApi::new();
-            slack_api
-                .send_message(stand_up.clone(), env::var("SLACK_CHANNEL")
-                .unwrap())
-                .await?;
+        // One target failing shouldn't keep the others or the timelog from going through
+        let stand_up = StandUp {
+            text: &stand_up,
+            context: &context,
+            date,
+            custom_template: template != template::DEFAULT_TEMPLATE,
+        };
+        let mut results = join_all(
+            notifiers
+                .iter()
+                .map(|notifier| async { (notifier.name(), notifier.send(&stand_up).await) })
+        ).await;
+        if self.timelog {
+            let timelog = self
+                .update_timelog(
+                    &config,
+                    date,
+                    tasks.tasks_for_timelog(&projects, grouped),
+                    tasks.projects_for_timelog(&projects),
+                )
+                .await;
+            results.push(("timelog", timelog));
         }
+        report(results)
+    }
+}
 
-        if self.timelog {
-            let sheet_api = GoogleSheetsApi::new(
-                env::var("SHEET_ID").unwrap(),
-            ).await;
-            sheet_api.post_timelog(
-                tasks.tasks_for_timelog(),
-                self.in_office,
-                self.hours,
-            ).await?;
+fn report(results: Vec<(&str, Result<(), Error>)>) -> Result<(), Error> {
+    let (succeeded, failed): (Vec<_>, Vec<_>) = results.into_iter().partition(|(_, result)| result.is_ok());
+    if failed.is_empty() {
+        return Ok(());
+    }
+    if !succeeded.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "Partial success: {} succeeded but {} failed",
+                succeeded.iter().map(|(name, _)| *name).collect::<Vec<&str>>().join(", "),
+                failed.iter().map(|(name, _)| *name).collect::<Vec<&str>>().join(", ")
+            )
+            .yellow()
+        );
+    }
+    let mut errors = failed.into_iter().filter_map(|(_, result)| result.err());
+    let first = errors.next();
+    for e in errors {
+        eprintln!("{}", e.to_string().red());
+    }
+    first.map_or(Ok(()), Err)
+}
+
+impl GenerateStandUp {
+    // First day whose work is included and the day the stand-up is for
+    fn date_range(&self, config: &Config) -> Result<(NaiveDate, NaiveDate), Error> {
+        let today = Local::now().date_naive();
+        let date = self.date.unwrap_or(today);
+        if date > today {
+            return Err(Error::Validation(format!("--date {} is in the future", date)));
         }
+        let since = match self.since {
+            Some(since) => since,
+            None if self.previous_working_day => config.work_calendar()?.previous_working_day(date),
+            None => date,
+        };
+        if since > date {
+            return Err(Error::Validation(format!(
+                "--since {} is after the stand-up date {}",
+                since, date
+            )));
+        }
+        Ok((since, date))
+    }
+
+    async fn update_timelog(
+        &self,
+        config: &Config,
+        date: NaiveDate,
+        tasks: String,
+        projects: Vec<String>,
+    ) -> Result<(), Error> {
+        let timelog = timelog::TimeLog::new(
+            config.timelog_columns()?,
+            tasks,
+            self.in_office.clone(),
+            self.hours.clone(),
+            projects,
+        );
+        let sheet_api = GoogleSheetsApi::new(config).await?;
+        sheet_api.post_timelog(date, timelog, self.timelog_mode).await?;
         Ok(())
     }
+
+    // Slack, plus every notify.targets with --notify, ready before anything is queried
+    fn notifiers(&self, config: &Config) -> Result<Vec<Box<dyn notifier::Notifier>>, Error> {
+        let mut targets = Vec::new();
+        if self.slack {
+            targets.push("slack".to_string());
+        }
+        if self.notify {
+            for target in notifier::configured_targets(config)? {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+        }
+        let slack_only = [("--thread-ts", self.thread_ts.is_some()), ("--update", self.update), ("--at", self.at.is_some())];
+        if let Some((flag, _)) = slack_only.iter().find(|(_, set)| *set) {
+            if !targets.iter().any(|target| target == "slack") {
+                return Err(Error::Validation(format!(
+                    "{} only applies to Slack, pass --slack or add slack to notify.targets",
+                    flag
+                )));
+            }
+        }
+        let slack_options = crate::slack::PostOptions {
+            thread_ts: self.thread_ts.clone(),
+            update: self.update,
+            post_at: self
+                .at
+                .as_deref()
+                .map(|at| calendar::parse_post_at(at, Local::now()))
+                .transpose()?,
+        };
+        notifier::from_targets(config, &targets, slack_options)
+    }
+}
+
+// Names of the projects `ids` from the project cache, fetching the pages of projects it doesn't know yet
+pub async fn project_names(config: &Config, ids: Vec<String>) -> Result<project_cache::ProjectNames, Error> {
+    let mut names = project_cache::ProjectCache::load(&config.project_cache()?)
+        .map(|cache| cache.names())
+        .unwrap_or_default();
+    for id in ids {
+        if names.contains_key(&id) {
+            continue;
+        }
+        let api = NotionApi::new(config, &format!("pages/{}", id))?;
+        match api.get_project().await {
+            Ok(page) => {
+                if let Some(title) = page.title() {
+                    names.insert(id, title);
+                }
+            }
+            Err(e) => println!("{}", format!("Could not get the name of project {}: {}", id, e).yellow()),
+        }
+    }
+    Ok(names)
 }
@@ -1,20 +1,31 @@
 use crate::prelude::*;
 
 #[derive(Debug, Args)]
-pub struct GiveMeArticle {}
+pub struct GiveMeArticle {
+    /// Only print the article url instead of opening it in the browser
+    #[arg(short, long)]
+    no_open: bool,
+}
 
 #[async_trait]
 impl RunCommand for GiveMeArticle {
-    async fn run(self) -> Result<(), Error> {
-        let database_id = env::var("NOTION_READING_LIST_DATABASE_ID").unwrap();
+    async fn run(self, config: Config) -> Result<(), Error> {
+        let database_id = config.require("notion.reading_list_database_id")?;
+        let reading_list_csv = config.require("files.reading_list_csv")?;
         let api = NotionApi::new(
+            &config,
             &format!(
                 "databases/{}/query",
                 database_id
             )
-        );
-        let url = api.get_article().await?;
-        open::that(&url)?;
+        )?;
+        let url = api.get_article(&reading_list_csv).await?;
+        // Without a browser the url printed below is all we need
+        if !self.no_open {
+            if let Err(e) = open::that(&url) {
+                println!("{}", format!("Could not open the browser: {}", e).yellow());
+            }
+        }
         println!("{}    {}","Your article is".green(), url.blue());
         Ok(())
     }
@@ -1,3 +1,8 @@
 pub mod give_me_article;
 pub mod generate_stand_up;
 pub mod add_task;
+pub mod config;
+pub mod projects;
+pub mod tasks;
+pub mod slack;
+pub mod timelog;
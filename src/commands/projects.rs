@@ -0,0 +1,49 @@
+use crate::prelude::*;
+
+#[derive(Debug, Args)]
+pub struct Projects {
+    #[command(subcommand)]
+    action: ProjectsAction,
+}
+
+#[derive(Debug, Subcommand)]
+enum ProjectsAction {
+    /// fetch the projects database from notion into the local cache
+    Sync,
+    /// list the projects available to add-task --project
+    List,
+}
+
+#[async_trait]
+impl RunCommand for Projects {
+    async fn run(self, config: Config) -> Result<(), Error> {
+        let cache_file = config.project_cache()?;
+        match self.action {
+            ProjectsAction::Sync => {
+                let database_id = config.require("notion.projects_database_id")?;
+                let api = NotionApi::new(
+                    &config,
+                    &format!(
+                        "databases/{}/query",
+                        database_id
+                    )
+                )?;
+                let cache = api.get_projects().await?;
+                cache.save(&cache_file)?;
+                println!(
+                    "{}    {}",
+                    format!("Synced {} projects to", cache.projects.len()).green(),
+                    cache_file.blue()
+                );
+            }
+            ProjectsAction::List => {
+                let cache = project_cache::ProjectCache::load(&cache_file)?;
+                for project in cache.projects.iter() {
+                    println!(" • {}    {}", project.name, project.id.dimmed());
+                }
+                println!("{}", format!("Last synced {}", cache.synced_at).dimmed());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use crate::prelude::*;
+use chrono::prelude::*;
+use post_log::PostLog;
+
+#[derive(Debug, Args)]
+pub struct SlackCommand {
+    #[command(subcommand)]
+    action: SlackAction,
+}
+
+#[derive(Debug, Subcommand)]
+enum SlackAction {
+    /// manage stand-ups scheduled with generate-stand-up --at
+    Scheduled {
+        #[command(subcommand)]
+        action: ScheduledAction,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ScheduledAction {
+    /// list the stand-ups Slack has yet to post
+    List,
+    /// cancel a scheduled stand-up
+    Cancel {
+        /// Scheduled message id or the stand-up date (YYYY-MM-DD)
+        id: String,
+    },
+}
+
+#[async_trait]
+impl RunCommand for SlackCommand {
+    async fn run(self, config: Config) -> Result<(), Error> {
+        let SlackAction::Scheduled { action } = self.action;
+        let posts_file = config.slack_posts()?;
+        let mut post_log = PostLog::load(&posts_file)?;
+        let now = Local::now().timestamp();
+
+        match action {
+            ScheduledAction::List => {
+                let pending = post_log.pending(now);
+                if pending.is_empty() {
+                    println!("{}", "No scheduled stand-ups".yellow());
+                }
+                for scheduled in pending {
+                    println!(
+                        " • {}  stand-up {}  {}",
+                        local_time(scheduled.post_at),
+                        scheduled.date,
+                        scheduled.id.dimmed()
+                    );
+                }
+            }
+            ScheduledAction::Cancel { id } => {
+                let scheduled = post_log
+                    .pending(now)
+                    .into_iter()
+                    .find(|scheduled| scheduled.id == id || scheduled.date == id)
+                    .cloned()
+                    .ok_or_else(|| Error::Validation(format!(
+                        "No scheduled stand-up `{}`, see `automate-me slack scheduled list`",
+                        id
+                    )))?;
+                let slack_api = SlackApi::new(&config)?;
+                slack_api
+                    .delete_scheduled_message(scheduled.channel.clone(), scheduled.id.clone())
+                    .await?;
+                if !config.dry_run {
+                    post_log.scheduled.retain(|other| other.id != scheduled.id);
+                    post_log.save(&posts_file)?;
+                }
+                println!(
+                    "{}    {}",
+                    format!("Cancelled the stand-up of {} scheduled for {}", scheduled.date, local_time(scheduled.post_at)).green(),
+                    scheduled.id.blue()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn local_time(timestamp: i64) -> String {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .earliest()
+        .map_or_else(|| timestamp.to_string(), |time| time.format("%a %Y-%m-%d %H:%M").to_string())
+}
@@ -0,0 +1,198 @@
+use crate::prelude::*;
+use chrono::{prelude::*, Duration};
+use stand_up::{Filter, Status, Task, TaskUpdate};
+
+// How far back `start`, `done` and `rename` look for the task to change
+const LOOKUP_DAYS: i64 = 30;
+
+#[derive(Debug, Args)]
+pub struct Tasks {
+    #[command(subcommand)]
+    action: TasksAction,
+}
+
+#[derive(Debug, Subcommand)]
+enum TasksAction {
+    /// list recently edited tasks with their short ids
+    List {
+        /// Only tasks with this status: "to do", "in progress", "done", "blocked" (the standup.blocked_status option)
+        #[arg(short, long)]
+        status: Option<String>,
+
+        /// Only tasks of this project
+        #[arg(short, long)]
+        project: Option<String>,
+
+        /// Only tasks edited on or after this date (YYYY-MM-DD) [default: a week ago]
+        #[arg(long)]
+        since: Option<NaiveDate>,
+    },
+    /// mark a task as in progress
+    Start {
+        /// Short id, page id or part of the task name
+        task: String,
+    },
+    /// mark a task as done
+    Done {
+        /// Short id, page id or part of the task name
+        task: String,
+    },
+    /// change the title of a task
+    Rename {
+        /// Short id, page id or part of the task name
+        task: String,
+
+        /// New title of the task
+        title: String,
+    },
+}
+
+#[async_trait]
+impl RunCommand for Tasks {
+    async fn run(self, config: Config) -> Result<(), Error> {
+        let database_id = config.require("notion.task_database_id")?;
+        let api = NotionApi::new(
+            &config,
+            &format!(
+                "databases/{}/query",
+                database_id
+            )
+        )?;
+
+        let (query, update) = match self.action {
+            TasksAction::List { status, project, since } => {
+                return list(&config, &api, status, project, since).await;
+            }
+            TasksAction::Start { task } => (task, TaskUpdate::status(Status::InProgress)),
+            TasksAction::Done { task } => (task, TaskUpdate::status(Status::Done)),
+            TasksAction::Rename { task, title } => (task, TaskUpdate::rename(title)),
+        };
+
+        // A full page id is patched as is, however long ago the task was edited
+        if let Some(page_id) = page_id(&query) {
+            let page_api = NotionApi::new(&config, &format!("pages/{}", page_id))?;
+            let page = page_api.update_task(&update).await?;
+            println!("{}    {}", format!("Updated {}", query).green(), page.url.blue());
+            return Ok(());
+        }
+
+        let since = Local::now().date_naive() - Duration::days(LOOKUP_DAYS);
+        let tasks = api.query_tasks(&Filter::for_tasks(None, None, since)).await?;
+        let task = find_task(&tasks, &query, config.get("standup.blocked_status"))?;
+        let page_api = NotionApi::new(&config, &format!("pages/{}", task.id))?;
+        let page = page_api.update_task(&update).await?;
+        println!("{}    {}", format!("Updated {}", task.name()).green(), page.url.blue());
+        Ok(())
+    }
+}
+
+async fn list(
+    config: &Config,
+    api: &NotionApi,
+    status: Option<String>,
+    project: Option<String>,
+    since: Option<NaiveDate>,
+) -> Result<(), Error> {
+    let blocked_status = config.get("standup.blocked_status");
+    let status = status.map(|status| Status::parse(&status, blocked_status)).transpose()?;
+    let projects = project_cache::ProjectCache::load(&config.project_cache()?).ok();
+    let project_id = match (project, &projects) {
+        (Some(project), Some(projects)) => Some(projects.resolve(&project)?.id.clone()),
+        (Some(_), None) => {
+            return Err(Error::Validation(
+                "--project needs the project cache, run `automate-me projects sync`".to_string()
+            ));
+        }
+        (None, _) => None,
+    };
+    let since = since.unwrap_or_else(|| Local::now().date_naive() - Duration::days(7));
+
+    let tasks: Vec<Task> = api
+        .query_tasks(&Filter::for_tasks(status, project_id, since))
+        .await?
+        .into_iter()
+        .filter(|task| task.status().is_tracked(blocked_status))
+        .collect();
+    if tasks.is_empty() {
+        println!("{}", "No tasks found".yellow());
+    }
+    for task in tasks.iter() {
+        let project_names: Vec<String> = task
+            .project_ids()
+            .iter()
+            .map(|id| {
+                projects
+                    .as_ref()
+                    .and_then(|projects| projects.name_of(id))
+                    .unwrap_or(id)
+                    .to_string()
+            })
+            .collect();
+        println!(
+            "{}  {:<12} {}  {}",
+            task.short_id().cyan(),
+            task.status().to_string(),
+            task.name(),
+            project_names.join(", ").dimmed()
+        );
+    }
+    Ok(())
+}
+
+// The 32 hex digits of a Notion page id, with or without dashes
+fn page_id(query: &str) -> Option<String> {
+    let id = query.trim().replace('-', "").to_lowercase();
+    Some(id).filter(|id| id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+// An id (or its first characters) wins, otherwise the name has to match a single task
+fn find_task<'a>(tasks: &'a [Task], query: &str, blocked_status: Option<&str>) -> Result<&'a Task, Error> {
+    let tasks: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| task.status().is_tracked(blocked_status))
+        .collect();
+    let id = query.replace('-', "").to_lowercase();
+    if id.len() >= 4 && id.chars().all(|c| c.is_ascii_hexdigit()) {
+        let by_id: Vec<&Task> = tasks
+            .iter()
+            .copied()
+            .filter(|task| task.id.replace('-', "").starts_with(&id))
+            .collect();
+        match by_id.as_slice() {
+            [] => {}
+            [task] => return Ok(task),
+            candidates => return Err(several_tasks(query, "more of the page id", candidates, |task| task.id.clone())),
+        }
+    }
+
+    let name = query.trim().to_lowercase();
+    if let Some(task) = tasks.iter().find(|task| task.name().to_lowercase() == name) {
+        return Ok(task);
+    }
+    let matching: Vec<&Task> = tasks
+        .iter()
+        .copied()
+        .filter(|task| task.name().to_lowercase().contains(&name))
+        .collect();
+    match matching.as_slice() {
+        [task] => Ok(task),
+        [] => Err(Error::Validation(format!(
+            "No task matching `{}` edited in the last {} days, see `automate-me tasks list` or pass the full page id",
+            query, LOOKUP_DAYS
+        ))),
+        candidates => Err(several_tasks(query, "a short id", candidates, Task::short_id)),
+    }
+}
+
+fn several_tasks(query: &str, use_instead: &str, candidates: &[&Task], id: impl Fn(&Task) -> String) -> Error {
+    Error::Validation(format!(
+        "`{}` matches several tasks, use {}:\n{}",
+        query,
+        use_instead,
+        candidates
+            .iter()
+            .map(|task| format!(" • {}  {}", id(task), task.name()))
+            .collect::<Vec<String>>()
+            .join("\n")
+    ))
+}
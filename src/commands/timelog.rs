@@ -0,0 +1,245 @@
+use crate::prelude::*;
+use crate::sheet::leave::LeaveLog;
+use crate::sheet::report::{self, MonthReport, ReportFormat};
+use crate::sheet::timelog::{TimeLog, TimelogMode, TimelogWrite};
+use chrono::prelude::*;
+use std::fs;
+
+#[derive(Debug, Args)]
+pub struct TimelogCommand {
+    #[command(subcommand)]
+    action: TimelogAction,
+}
+
+#[derive(Debug, Subcommand)]
+enum TimelogAction {
+    /// record a day of leave and mark it in the timelog sheet
+    Leave {
+        /// Day of leave (YYYY-MM-DD)
+        date: NaiveDate,
+
+        /// Why you are off, shown on the day's row
+        #[arg(short, long)]
+        reason: Option<String>,
+    },
+    /// fill the rows of working days the timelog is missing
+    Backfill(Backfill),
+    /// sum up a month of the timelog and export it
+    Report(Report),
+}
+
+#[derive(Debug, Args)]
+struct Backfill {
+    /// First day to fill (YYYY-MM-DD)
+    #[arg(long)]
+    from: NaiveDate,
+
+    /// Last day to fill (YYYY-MM-DD) [default: yesterday]
+    #[arg(long)]
+    to: Option<NaiveDate>,
+
+    /// data to fill out in timelog In Office header
+    #[arg(short, long, default_value = "WFH")]
+    in_office: String,
+
+    /// data to fill out in timelog Hours header
+    #[arg(short = 'w', long, default_value = "8")]
+    hours: String,
+
+    /// Don't group the tasks under their project
+    #[arg(long)]
+    no_group: bool,
+}
+
+#[derive(Debug, Args)]
+struct Report {
+    /// Month to report on (YYYY-MM) [default: this month]
+    #[arg(short, long, value_parser = parse_month)]
+    month: Option<NaiveDate>,
+
+    /// Export format, guessed from the extension of --output when left out
+    #[arg(short, long, value_enum)]
+    format: Option<ReportFormat>,
+
+    /// File to write the export to, without it the export is printed
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Hourly rate, adds the amounts to the export
+    #[arg(short, long)]
+    rate: Option<f64>,
+}
+
+#[async_trait]
+impl RunCommand for TimelogCommand {
+    async fn run(self, config: Config) -> Result<(), Error> {
+        match self.action {
+            TimelogAction::Leave { date, reason } => leave(&config, date, reason).await,
+            TimelogAction::Backfill(backfill) => backfill.run(&config).await,
+            TimelogAction::Report(report) => report.run(&config).await,
+        }
+    }
+}
+
+async fn leave(config: &Config, date: NaiveDate, reason: Option<String>) -> Result<(), Error> {
+    let columns = config.timelog_columns()?;
+    let leave_file = config.leave()?;
+    let mut leave_log = LeaveLog::load(&leave_file)?;
+    let day_off = leave_log.add(date, reason);
+    if config.dry_run {
+        print_dry_run(&format!("Would record leave in {}", leave_file), &format!("{}  {}", date, day_off.label));
+    } else {
+        leave_log.save(&leave_file)?;
+        println!("{}", format!("Recorded leave on {}", date).green());
+    }
+
+    let sheet_api = GoogleSheetsApi::new(config).await?;
+    sheet_api.mark_day_off(TimeLog::layout(columns), &day_off).await
+}
+
+impl Backfill {
+    // Each working day gets the tasks edited on it, rows with anything in them are left alone
+    async fn run(self, config: &Config) -> Result<(), Error> {
+        let today = Local::now().date_naive();
+        let to = self.to.unwrap_or(today - chrono::Duration::days(1));
+        if self.from > to {
+            return Err(Error::Validation(format!("--from {} is after --to {}", self.from, to)));
+        }
+        if to > today {
+            return Err(Error::Validation(format!("--to {} is in the future", to)));
+        }
+        let database_id = config.require("notion.task_database_id")?;
+        let columns = config.timelog_columns()?;
+        let calendar = config.work_calendar()?;
+        let api = NotionApi::new(config, &format!("databases/{}/query", database_id))?;
+        let sheet_api = GoogleSheetsApi::new(config).await?;
+
+        let (mut written, mut filled, mut no_tasks, mut failed) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        let (mut days, mut unqueried) = (Vec::new(), Vec::new());
+        let mut days_off = 0;
+        for date in self.from.iter_days().take_while(|date| *date <= to) {
+            if !calendar.is_working_day(date) {
+                days_off += 1;
+                continue;
+            }
+            // A failed query doesn't stop the other days either, they are all reported at the end
+            match api.get_tasks(date, date, config.get("standup.blocked_status")).await {
+                Ok(tasks) => days.push((date, tasks)),
+                Err(e) => {
+                    eprintln!("{}", format!("Could not get the tasks of {}: {}", date, e).red());
+                    unqueried.push(date);
+                }
+            }
+        }
+        // Names are looked up for all the days at once, so each unknown project is fetched a single time
+        let mut project_ids: Vec<String> = days.iter().flat_map(|(_, tasks)| tasks.project_ids()).collect();
+        project_ids.sort();
+        project_ids.dedup();
+        let projects = generate_stand_up::project_names(config, project_ids).await?;
+
+        for (date, tasks) in days {
+            println!("{}", format!("Backfilling {}", date).yellow());
+            let task = tasks.tasks_for_timelog(&projects, !self.no_group);
+            if task.is_empty() {
+                no_tasks.push(date);
+                continue;
+            }
+            let timelog = TimeLog::new(
+                columns.clone(),
+                task,
+                self.in_office.clone(),
+                self.hours.clone(),
+                tasks.projects_for_timelog(&projects),
+            );
+            // A rejected write doesn't stop the other days, they are all reported at the end
+            match sheet_api.post_timelog(date, timelog, TimelogMode::SkipIfFilled).await {
+                Ok(TimelogWrite::Written) => written.push(date),
+                Ok(TimelogWrite::AlreadyFilled) => filled.push(date),
+                Err(e) => {
+                    eprintln!("{}", e.to_string().red());
+                    failed.push(date);
+                }
+            }
+        }
+
+        println!("{}", format!("Filled {} day(s): {}", written.len(), dates(&written)).green());
+        if !filled.is_empty() {
+            println!("{}", format!("Skipped {} day(s) that already had content: {}", filled.len(), dates(&filled)).yellow());
+        }
+        if !no_tasks.is_empty() {
+            println!("{}", format!("Skipped {} day(s) without tasks: {}", no_tasks.len(), dates(&no_tasks)).yellow());
+        }
+        if days_off > 0 {
+            println!("{}", format!("Skipped {} day(s) off (weekends, holidays and leave)", days_off).dimmed());
+        }
+        if !failed.is_empty() {
+            let error = Error::sheets(format!("could not write the timelog of {}", dates(&failed)));
+            if unqueried.is_empty() {
+                return Err(error);
+            }
+            eprintln!("{}", error.to_string().red());
+        }
+        if !unqueried.is_empty() {
+            return Err(Error::notion(format!("could not get the tasks of {}", dates(&unqueried))));
+        }
+        Ok(())
+    }
+}
+
+impl Report {
+    async fn run(self, config: &Config) -> Result<(), Error> {
+        let today = Local::now().date_naive();
+        let month = self.month.unwrap_or(today.with_day(1).unwrap());
+        let format = match (self.format, &self.output) {
+            (Some(format), _) => Some(format),
+            (None, Some(output)) => Some(ReportFormat::from_path(output).ok_or_else(|| {
+                Error::Validation(format!("Can't tell the export format of {}, pass --format", output))
+            })?),
+            (None, None) => None,
+        };
+        let columns = config.timelog_columns()?;
+        let calendar = config.work_calendar()?;
+        let sheet_api = GoogleSheetsApi::new(config).await?;
+        let rows = sheet_api.read_month(month, &columns).await?;
+        let report = MonthReport::new(month, &columns, &rows, &calendar, today);
+
+        // A bare --format is meant to be piped, so only the export is printed
+        if let (Some(format), None) = (format, &self.output) {
+            print!("{}", report.export(format, self.rate)?);
+            return Ok(());
+        }
+        print_report(&report, self.rate);
+        if let (Some(format), Some(output)) = (format, &self.output) {
+            fs::write(output, report.export(format, self.rate)?).map_err(|e| Error::local_file(output, e))?;
+            println!("{}", format!("Exported the report to {}", output).green());
+        }
+        Ok(())
+    }
+}
+
+fn print_report(report: &MonthReport, rate: Option<f64>) {
+    println!("{}", format!("Timelog of {}", report.month).yellow());
+    println!("Hours:        {}", report::decimal(report.total_hours));
+    if let Some(rate) = rate {
+        println!("Amount:       {}", report::decimal(report.total_hours * rate));
+    }
+    println!("Office days:  {}", report.office_days);
+    println!("WFH days:     {}", report.wfh_days);
+    if report.empty_days.is_empty() {
+        println!("{}", "No empty working days".green());
+    } else {
+        println!("{}", format!("Empty working days: {}", dates(&report.empty_days)).red());
+    }
+    for warning in &report.warnings {
+        println!("{}", warning.yellow());
+    }
+}
+
+fn parse_month(month: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| format!("`{}` is not a YYYY-MM month", month))
+}
+
+fn dates(dates: &[NaiveDate]) -> String {
+    dates.iter().map(|date| date.to_string()).collect::<Vec<String>>().join(", ")
+}
@@ -0,0 +1,468 @@
+use crate::prelude::*;
+use crate::calendar;
+use crate::notifier;
+use crate::sheet::timelog;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+pub struct Setting {
+    pub key: &'static str,
+    pub env: &'static str,
+    pub description: &'static str,
+    pub secret: bool,
+    pub required: bool,
+}
+
+// Every setting, the env var that overrides it and what it is for
+pub const SETTINGS: &[Setting] = &[
+    Setting { key: "notion.api_key", env: "NOTION_API_KEY", description: "secret of your Notion internal integration", secret: true, required: true },
+    Setting { key: "notion.api_url", env: "NOTION_API_URL", description: "Notion API endpoint", secret: false, required: false },
+    Setting { key: "notion.task_database_id", env: "NOTION_TASK_DATABASE_ID", description: "id of the Notion task manager database", secret: false, required: true },
+    Setting { key: "notion.projects_database_id", env: "NOTION_PROJECTS_DATABASE_ID", description: "id of the Notion projects database tasks relate to", secret: false, required: true },
+    Setting { key: "notion.reading_list_database_id", env: "NOTION_READING_LIST_DATABASE_ID", description: "id of the Notion reading list database", secret: false, required: true },
+    Setting { key: "slack.user_token", env: "SLACK_USER_TOKEN", description: "Slack user OAuth token with chat:write scope", secret: true, required: true },
+    Setting { key: "slack.channel", env: "SLACK_CHANNEL", description: "Slack channel id to post the stand up to", secret: false, required: true },
+    Setting { key: "slack.thread_pattern", env: "SLACK_THREAD_PATTERN", description: "text of the daily thread to post the stand-up under", secret: false, required: false },
+    Setting { key: "slack.api_url", env: "SLACK_API_URL", description: "Slack API endpoint", secret: false, required: false },
+    Setting { key: "notify.targets", env: "NOTIFY_TARGETS", description: "where --notify sends the stand-up: slack, discord, teams, email", secret: false, required: false },
+    Setting { key: "notify.discord_webhook", env: "DISCORD_WEBHOOK_URL", description: "Discord channel webhook url", secret: true, required: false },
+    Setting { key: "notify.teams_webhook", env: "TEAMS_WEBHOOK_URL", description: "Teams incoming webhook url", secret: true, required: false },
+    Setting { key: "notify.smtp_host", env: "SMTP_HOST", description: "SMTP relay the stand-up email goes through", secret: false, required: false },
+    Setting { key: "notify.smtp_port", env: "SMTP_PORT", description: "port of the SMTP relay", secret: false, required: false },
+    Setting { key: "notify.email_from", env: "EMAIL_FROM", description: "sender of the stand-up email", secret: false, required: false },
+    Setting { key: "notify.email_to", env: "EMAIL_TO", description: "comma separated recipients of the stand-up email", secret: false, required: false },
+    Setting { key: "sheets.sheet_id", env: "SHEET_ID", description: "id of the Google spreadsheet used for the timelog", secret: false, required: true },
+    Setting { key: "sheets.service_account_file", env: "SERVICE_ACCOUNT_FILE", description: "path to the Google service account key json", secret: false, required: false },
+    Setting { key: "sheets.access_token", env: "GOOGLE_ACCESS_TOKEN", description: "pre-issued token used instead of the service account", secret: true, required: false },
+    Setting { key: "sheets.api_url", env: "SHEETS_API_URL", description: "Google Sheets API endpoint", secret: false, required: false },
+    Setting { key: "standup.template", env: "STANDUP_TEMPLATE", description: "handlebars template file for the stand-up message", secret: false, required: false },
+    Setting { key: "standup.blocked_status", env: "STANDUP_BLOCKED_STATUS", description: "Notion status option of blocked tasks, e.g. Blocked, listed as blockers", secret: false, required: false },
+    Setting { key: "standup.holidays", env: "STANDUP_HOLIDAYS", description: "comma separated YYYY-MM-DD dates skipped as working days, added to standup.holiday_calendar", secret: false, required: false },
+    Setting { key: "standup.holiday_calendar", env: "HOLIDAY_CALENDAR", description: "holidays as an .ics file or a YAML list of date and label", secret: false, required: false },
+    Setting { key: "standup.weekend", env: "STANDUP_WEEKEND", description: "comma separated days off every week, Sat, Sun by default", secret: false, required: false },
+    Setting { key: "files.reading_list_csv", env: "READING_LIST_CSV", description: "path to the local reading list csv", secret: false, required: true },
+    Setting { key: "files.project_cache_json", env: "PROJECT_CACHE_JSON", description: "where `projects sync` caches the Notion projects", secret: false, required: false },
+    Setting { key: "files.slack_posts_json", env: "SLACK_POSTS_JSON", description: "where posted and scheduled stand-ups are remembered", secret: false, required: false },
+    Setting { key: "files.leave_json", env: "LEAVE_JSON", description: "where `timelog leave` records leave days", secret: false, required: false },
+];
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub notion: NotionConfig,
+    pub slack: SlackConfig,
+    pub notify: NotifyConfig,
+    pub sheets: SheetsConfig,
+    pub standup: StandupConfig,
+    pub files: FilesConfig,
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(skip)]
+    pub profile: Option<String>,
+    #[serde(skip)]
+    pub dry_run: bool,
+    #[serde(skip)]
+    raw: Table,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NotionConfig {
+    pub api_key: Option<String>,
+    pub api_url: Option<String>,
+    pub task_database_id: Option<String>,
+    pub reading_list_database_id: Option<String>,
+    pub projects_database_id: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SlackConfig {
+    pub user_token: Option<String>,
+    pub channel: Option<String>,
+    pub thread_pattern: Option<String>,
+    pub api_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    pub targets: Option<String>,
+    pub discord_webhook: Option<String>,
+    pub teams_webhook: Option<String>,
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<String>,
+    pub email_from: Option<String>,
+    pub email_to: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SheetsConfig {
+    pub sheet_id: Option<String>,
+    pub service_account_file: Option<String>,
+    pub access_token: Option<String>,
+    pub api_url: Option<String>,
+    pub columns: Vec<timelog::TimelogColumn>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StandupConfig {
+    pub template: Option<String>,
+    pub blocked_status: Option<String>,
+    pub holidays: Option<String>,
+    pub holiday_calendar: Option<String>,
+    pub weekend: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FilesConfig {
+    pub reading_list_csv: Option<String>,
+    pub project_cache_json: Option<String>,
+    pub slack_posts_json: Option<String>,
+    pub leave_json: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Source {
+    Env,
+    File,
+    Unset,
+}
+
+impl Config {
+    // Load the config file, apply the profile on top of it and then the env overrides
+    pub fn load(path: Option<PathBuf>, profile: Option<String>) -> Result<Self, Error> {
+        let path = path_or_default(path)?;
+        let mut table = read_table(&path)?;
+        let profiles = match table.remove("profiles") {
+            Some(Value::Table(profiles)) => profiles,
+            Some(_) => return Err(Error::config("profiles", "must be a table of named profiles")),
+            None => Table::new(),
+        };
+
+        if let Some(name) = &profile {
+            match profiles.get(name) {
+                Some(Value::Table(overrides)) => merge(&mut table, overrides),
+                _ => {
+                    let known: Vec<&str> = profiles.keys().map(|name| name.as_str()).collect();
+                    return Err(Error::config("profile", format!(
+                        "no profile named `{}` in {}, known profiles: {}",
+                        name,
+                        path.display(),
+                        if known.is_empty() { "none".to_string() } else { known.join(", ") }
+                    )));
+                }
+            }
+        }
+
+        settings_to_strings(&mut table);
+        let raw = table.clone();
+        for setting in SETTINGS {
+            if let Some(value) = env_value(setting) {
+                let (section, field) = split_key(setting.key);
+                let section = table
+                    .entry(section)
+                    .or_insert_with(|| Value::Table(Table::new()));
+                if let Value::Table(section) = section {
+                    section.insert(field.to_string(), Value::String(value));
+                }
+            }
+        }
+
+        let mut config: Config = Value::Table(table)
+            .try_into()
+            .map_err(|e| Error::config("config", format!("{} in {}", e, path.display())))?;
+        config.path = path;
+        config.profile = profile;
+        config.raw = raw;
+        Ok(config)
+    }
+
+    // Look up a setting by its dotted key, e.g. "notion.api_key"
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let value = match key {
+            "notion.api_key" => &self.notion.api_key,
+            "notion.api_url" => &self.notion.api_url,
+            "notion.task_database_id" => &self.notion.task_database_id,
+            "notion.reading_list_database_id" => &self.notion.reading_list_database_id,
+            "notion.projects_database_id" => &self.notion.projects_database_id,
+            "slack.user_token" => &self.slack.user_token,
+            "slack.channel" => &self.slack.channel,
+            "slack.thread_pattern" => &self.slack.thread_pattern,
+            "slack.api_url" => &self.slack.api_url,
+            "notify.targets" => &self.notify.targets,
+            "notify.discord_webhook" => &self.notify.discord_webhook,
+            "notify.teams_webhook" => &self.notify.teams_webhook,
+            "notify.smtp_host" => &self.notify.smtp_host,
+            "notify.smtp_port" => &self.notify.smtp_port,
+            "notify.email_from" => &self.notify.email_from,
+            "notify.email_to" => &self.notify.email_to,
+            "sheets.sheet_id" => &self.sheets.sheet_id,
+            "sheets.service_account_file" => &self.sheets.service_account_file,
+            "sheets.access_token" => &self.sheets.access_token,
+            "sheets.api_url" => &self.sheets.api_url,
+            "standup.template" => &self.standup.template,
+            "standup.blocked_status" => &self.standup.blocked_status,
+            "standup.holidays" => &self.standup.holidays,
+            "standup.holiday_calendar" => &self.standup.holiday_calendar,
+            "standup.weekend" => &self.standup.weekend,
+            "files.reading_list_csv" => &self.files.reading_list_csv,
+            "files.project_cache_json" => &self.files.project_cache_json,
+            "files.slack_posts_json" => &self.files.slack_posts_json,
+            "files.leave_json" => &self.files.leave_json,
+            _ => &None,
+        };
+        value.as_deref().filter(|value| !value.trim().is_empty())
+    }
+
+    // Same as `get` but missing settings are a config error explaining how to set them
+    pub fn require(&self, key: &str) -> Result<String, Error> {
+        self.get(key).map(|value| value.to_string()).ok_or_else(|| {
+            let setting = setting(key);
+            Error::config(key, format!(
+                "set `{}` in {} or the {} env var ({})",
+                key,
+                self.path.display(),
+                setting.map_or("matching", |setting| setting.env),
+                setting.map_or("required setting", |setting| setting.description),
+            ))
+        })
+    }
+
+    pub fn source(&self, key: &str) -> Source {
+        let from_env = setting(key).and_then(env_value).is_some();
+        let (section, field) = split_key(key);
+        let in_file = self
+            .raw
+            .get(section)
+            .and_then(|section| section.get(field))
+            .is_some();
+        if from_env {
+            Source::Env
+        } else if in_file {
+            Source::File
+        } else {
+            Source::Unset
+        }
+    }
+
+    // Everything that would make a command fail, without touching the network
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for setting in SETTINGS.iter().filter(|setting| setting.required) {
+            if self.get(setting.key).is_none() {
+                problems.push(format!("{} is not set ({}, env {})", setting.key, setting.description, setting.env));
+            }
+        }
+        if self.get("sheets.service_account_file").is_none() && self.get("sheets.access_token").is_none() {
+            problems.push("sheets.service_account_file or sheets.access_token must be set".to_string());
+        }
+        if let Err(e) = notifier::configured_targets(self) {
+            problems.push(e.to_string());
+        }
+        for key in ["notion.api_url", "slack.api_url", "notify.discord_webhook", "notify.teams_webhook", "sheets.api_url"] {
+            if let Some(url) = self.get(key) {
+                if reqwest::Url::parse(url).is_err() {
+                    problems.push(format!("{} is not a valid url: {}", key, url));
+                }
+            }
+        }
+        for key in ["sheets.service_account_file", "standup.template", "standup.holiday_calendar", "files.reading_list_csv"] {
+            if let Some(file) = self.get(key) {
+                if !Path::new(file).is_file() {
+                    problems.push(format!("{} points to a missing file: {}", key, file));
+                }
+            }
+        }
+        if let Err(e) = self.work_calendar() {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = self.timelog_columns() {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = self.project_cache() {
+            problems.push(e.to_string());
+        }
+        problems
+    }
+
+    // Worth knowing but no reason to fail validation, e.g. state a command creates on its first run
+    pub fn notes(&self) -> Vec<String> {
+        let mut notes = Vec::new();
+        if let Ok(file) = self.project_cache() {
+            if !Path::new(&file).is_file() {
+                notes.push(format!("no project cache at {} yet, run `automate-me projects sync` to fetch it", file));
+            }
+        }
+        notes
+    }
+
+    // standup.weekend, standup.holidays, standup.holiday_calendar and the leave in files.leave_json
+    pub fn work_calendar(&self) -> Result<calendar::WorkCalendar, Error> {
+        let weekend = match self.get("standup.weekend") {
+            Some(weekend) => calendar::parse_weekend(weekend)?,
+            None => vec![chrono::Weekday::Sat, chrono::Weekday::Sun],
+        };
+        let leave_file = self.leave()?;
+        let mut days_off = leave::LeaveLog::load(&leave_file)?.days_off(&leave_file)?;
+        // standup.holidays adds its dates to the calendar, the calendar's label wins on a day both have
+        let mut holidays = match self.get("standup.holiday_calendar") {
+            Some(file) => calendar::read_holiday_calendar(file)?,
+            None => Vec::new(),
+        };
+        if let Some(dates) = self.get("standup.holidays") {
+            for date in calendar::parse_holidays(dates)? {
+                if !holidays.iter().any(|holiday| holiday.date == date) {
+                    holidays.push(calendar::DayOff {
+                        date,
+                        kind: calendar::DayOffKind::Holiday,
+                        label: "Holiday".to_string(),
+                    });
+                }
+            }
+        }
+        days_off.extend(holidays);
+        Ok(calendar::WorkCalendar { weekend, days_off })
+    }
+
+    // sheets.columns, defaulting to the Date, In Office, Task and hrs columns
+    pub fn timelog_columns(&self) -> Result<Vec<timelog::TimelogColumn>, Error> {
+        if self.sheets.columns.is_empty() {
+            return Ok(timelog::default_columns());
+        }
+        timelog::check_columns(&self.sheets.columns).map_err(|e| Error::config("sheets.columns", e))?;
+        Ok(self.sheets.columns.clone())
+    }
+
+    // files.project_cache_json, defaulting to $XDG_CACHE_HOME/automate-me/projects.json
+    pub fn project_cache(&self) -> Result<String, Error> {
+        if let Some(file) = self.get("files.project_cache_json") {
+            return Ok(file.to_string());
+        }
+        dirs::cache_dir()
+            .map(|dir| dir.join("automate-me").join("projects.json").display().to_string())
+            .ok_or_else(|| Error::config("files.project_cache_json", "could not find the cache directory, set it explicitly"))
+    }
+
+    // files.slack_posts_json, defaulting to $XDG_DATA_HOME/automate-me/slack_posts.json
+    pub fn slack_posts(&self) -> Result<String, Error> {
+        if let Some(file) = self.get("files.slack_posts_json") {
+            return Ok(file.to_string());
+        }
+        dirs::data_dir()
+            .map(|dir| dir.join("automate-me").join("slack_posts.json").display().to_string())
+            .ok_or_else(|| Error::config("files.slack_posts_json", "could not find the data directory, set it explicitly"))
+    }
+
+    // files.leave_json, defaulting to $XDG_DATA_HOME/automate-me/leave.json
+    pub fn leave(&self) -> Result<String, Error> {
+        if let Some(file) = self.get("files.leave_json") {
+            return Ok(file.to_string());
+        }
+        dirs::data_dir()
+            .map(|dir| dir.join("automate-me").join("leave.json").display().to_string())
+            .ok_or_else(|| Error::config("files.leave_json", "could not find the data directory, set it explicitly"))
+    }
+}
+
+pub fn setting(key: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|setting| setting.key == key)
+}
+
+// The --config path, or the default one
+pub fn path_or_default(path: Option<PathBuf>) -> Result<PathBuf, Error> {
+    match path {
+        Some(path) => Ok(path),
+        None => default_path(),
+    }
+}
+
+// $XDG_CONFIG_HOME/automate-me/config.toml unless AUTOMATE_ME_CONFIG says otherwise
+pub fn default_path() -> Result<PathBuf, Error> {
+    if let Ok(path) = env::var("AUTOMATE_ME_CONFIG") {
+        return Ok(PathBuf::from(path));
+    }
+    dirs::config_dir()
+        .map(|dir| dir.join("automate-me").join("config.toml"))
+        .ok_or_else(|| Error::config("config", "could not find the config directory, pass --config <path>"))
+}
+
+pub fn template() -> String {
+    let mut template = String::from("# automate-me config, every setting can be overridden by its env var\n");
+    let mut current_section = "";
+    for setting in SETTINGS {
+        let (section, field) = split_key(setting.key);
+        if section != current_section {
+            template.push_str(&format!("\n[{}]\n", section));
+            current_section = section;
+        }
+        template.push_str(&format!("# {} (env {})\n", setting.description, setting.env));
+        if setting.required {
+            template.push_str(&format!("{} = \"\"\n", field));
+        } else {
+            template.push_str(&format!("# {} = \"\"\n", field));
+        }
+    }
+    template.push_str("\n# timelog columns in sheet order, the default is Date, In Office, Task and hrs\n");
+    template.push_str("# source: date (first column), in_office, task, hours, projects, text (with value) or manual\n");
+    template.push_str("# [[sheets.columns]]\n# name = \"Billable\"\n# source = \"text\"\n# value = \"Yes\"\n# width = 80\n");
+    template.push_str("\n# named profiles override any of the above, use with --profile work\n");
+    template.push_str("# [profiles.work.slack]\n# channel = \"\"\n");
+    template
+}
+
+fn read_table(path: &Path) -> Result<Table, Error> {
+    if !path.exists() {
+        return Ok(Table::new());
+    }
+    let contents = fs::read_to_string(path).map_err(|e| Error::local_file(&path.display().to_string(), e))?;
+    contents
+        .parse::<Table>()
+        .map_err(|e| Error::config("config", format!("{} is not valid TOML: {}", path.display(), e)))
+}
+
+fn merge(base: &mut Table, overrides: &Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(key), value) {
+            (Some(Value::Table(base)), Value::Table(overrides)) => merge(base, overrides),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+// Settings are read as text like their env vars, so `smtp_port = 25` is taken as "25"
+// and `targets = ["slack", "discord"]` as "slack, discord"
+fn settings_to_strings(table: &mut Table) {
+    for setting in SETTINGS {
+        let (section, field) = split_key(setting.key);
+        if let Some(Value::Table(section)) = table.get_mut(section) {
+            match section.get_mut(field) {
+                Some(value @ (Value::Integer(_) | Value::Float(_))) => *value = Value::String(value.to_string()),
+                Some(value @ Value::Array(_)) => {
+                    let items: Vec<String> = value
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|item| item.as_str().map_or_else(|| item.to_string(), str::to_string))
+                        .collect();
+                    *value = Value::String(items.join(", "));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn env_value(setting: &Setting) -> Option<String> {
+    env::var(setting.env).ok().filter(|value| !value.trim().is_empty())
+}
+
+fn split_key(key: &str) -> (&str, &str) {
+    key.split_once('.').unwrap_or(("", key))
+}
@@ -0,0 +1,101 @@
+use std::fmt::Display;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Missing or invalid config `{key}`: {hint}")]
+    Config { key: String, hint: String },
+
+    #[error("Notion API request failed: {0}\nCheck notion.api_key and that the integration is shared with the database")]
+    NotionApi(String),
+
+    #[error("Notion returned {status} {code}: {message}{}", notion_hint(.code))]
+    NotionResponse { status: u16, code: String, message: String },
+
+    #[error("Slack API request failed: {0}\nCheck slack.user_token and slack.channel")]
+    SlackApi(String),
+
+    #[error("Slack {method} returned `{error}`{}", slack_hint(.error))]
+    SlackResponse { method: String, error: String },
+
+    #[error("Sending the stand-up to {target} failed: {message}\nCheck the notify settings of {target}")]
+    Notify { target: String, message: String },
+
+    #[error("Google Sheets API request failed: {0}\nCheck sheets.sheet_id and that the service account can edit the sheet")]
+    SheetsApi(String),
+
+    #[error("Could not use local file `{path}`: {message}")]
+    LocalFile { path: String, message: String },
+
+    #[error("{0}")]
+    Validation(String),
+}
+
+impl Error {
+    pub fn config(key: &str, hint: impl Display) -> Self {
+        Error::Config {
+            key: key.to_string(),
+            hint: hint.to_string(),
+        }
+    }
+
+    pub fn notion(error: impl Display) -> Self {
+        Error::NotionApi(error.to_string())
+    }
+
+    pub fn slack(error: impl Display) -> Self {
+        Error::SlackApi(error.to_string())
+    }
+
+    pub fn notify(target: &str, error: impl Display) -> Self {
+        Error::Notify {
+            target: target.to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    pub fn sheets(error: impl Display) -> Self {
+        Error::SheetsApi(error.to_string())
+    }
+
+    pub fn local_file(path: &str, error: impl Display) -> Self {
+        Error::LocalFile {
+            path: path.to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    // Each variant gets its own exit code so wrappers can tell failures apart
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Config { .. } => 2,
+            Error::Validation(_) => 3,
+            Error::LocalFile { .. } => 4,
+            Error::NotionApi(_) | Error::NotionResponse { .. } => 5,
+            Error::SlackApi(_) | Error::SlackResponse { .. } => 6,
+            Error::SheetsApi(_) => 7,
+            Error::Notify { .. } => 8,
+        }
+    }
+}
+
+fn notion_hint(code: &str) -> &'static str {
+    match code {
+        "unauthorized" => "\nCheck notion.api_key",
+        "restricted_resource" | "object_not_found" => "\nMake sure the database is shared with your integration",
+        "validation_error" => "\nCheck the property names, and run `automate-me projects sync` if the project was renamed or added since the last sync",
+        "rate_limited" => "\nNotion is rate limiting requests, try again in a minute",
+        _ => "",
+    }
+}
+
+fn slack_hint(error: &str) -> &'static str {
+    match error {
+        "not_in_channel" => "\nInvite yourself to slack.channel or pick a channel you are a member of",
+        "invalid_auth" | "not_authed" | "token_revoked" | "token_expired" => "\nCheck slack.user_token",
+        "channel_not_found" => "\nslack.channel must be a channel id (like C0123456) visible to the token",
+        "missing_scope" => "\nThe token needs the chat:write and channels:history scopes",
+        "message_not_found" | "cant_update_message" => "\nThe stand-up to update was deleted or posted by someone else",
+        "ratelimited" => "\nSlack is rate limiting requests, try again in a minute",
+        _ => "",
+    }
+}
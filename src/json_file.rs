@@ -0,0 +1,26 @@
+use crate::prelude::*;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::Path;
+
+// The state files kept between runs (project cache, Slack posts, leave) are pretty-printed JSON
+
+// None when the file doesn't exist yet
+pub fn load_json<T: DeserializeOwned>(file_path: &str) -> Result<Option<T>, Error> {
+    if !Path::new(file_path).exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(file_path).map_err(|e| Error::local_file(file_path, e))?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| Error::local_file(file_path, e))
+}
+
+// Creates the directories of `file_path` it needs
+pub fn save_json<T: Serialize>(file_path: &str, value: &T) -> Result<(), Error> {
+    if let Some(dir) = Path::new(file_path).parent() {
+        fs::create_dir_all(dir).map_err(|e| Error::local_file(file_path, e))?;
+    }
+    let contents = serde_json::to_string_pretty(value).map_err(|e| Error::local_file(file_path, e))?;
+    fs::write(file_path, contents).map_err(|e| Error::local_file(file_path, e))
+}
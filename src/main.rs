@@ -1,6 +1,11 @@
 use dotenv::dotenv;
+mod calendar;
 mod cli;
 mod commands;
+mod config;
+mod error;
+mod json_file;
+mod notifier;
 mod notion;
 mod slack;
 mod sheet;
@@ -14,16 +19,18 @@ mod prelude {
     pub use clap::{Parser, Subcommand, Args};
     pub use colored::Colorize;
     pub use crate::commands::*;
-    pub use anyhow::Error;
+    pub use crate::error::Error;
+    pub use crate::config::Config;
     pub use serde::{Deserialize, Serialize};
     pub use async_trait::async_trait;
     pub use csv::{ReaderBuilder, WriterBuilder};
 }
 
 use prelude::*;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     dotenv().ok();
-    Cli::parse().run().await;
+    Cli::parse().run().await
 }
@@ -0,0 +1,61 @@
+use crate::prelude::*;
+use super::{chunks, Notifier, StandUp};
+use reqwest::Client;
+
+// Discord rejects messages longer than this
+const CONTENT_LIMIT: usize = 2000;
+
+pub struct DiscordWebhook {
+    client: Client,
+    url: String,
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookMessage {
+    content: String,
+}
+
+impl DiscordWebhook {
+    pub fn new(config: &Config) -> Result<Self, Error> {
+        Ok(Self {
+            client: Client::new(),
+            url: config.require("notify.discord_webhook")?,
+            dry_run: config.dry_run,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordWebhook {
+    fn name(&self) -> &'static str {
+        "Discord"
+    }
+
+    async fn send(&self, stand_up: &StandUp<'_>) -> Result<(), Error> {
+        println!("{}", "Sending message to Discord".yellow());
+        for content in chunks(stand_up.text, CONTENT_LIMIT) {
+            let message = WebhookMessage { content };
+            if self.dry_run {
+                print_dry_run(
+                    "Would POST to the Discord webhook",
+                    &serde_json::to_string_pretty(&message).map_err(|e| Error::notify(self.name(), e))?,
+                );
+                continue;
+            }
+            let response = self.client
+                .post(&self.url)
+                .json(&message)
+                .send()
+                .await
+                .map_err(|e| Error::notify(self.name(), e))?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(Error::notify(self.name(), format!("{} {}", status, body)));
+            }
+        }
+        println!("{}", "Message sent to Discord".green());
+        Ok(())
+    }
+}
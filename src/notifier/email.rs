@@ -0,0 +1,88 @@
+use crate::prelude::*;
+use super::{Notifier, StandUp};
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+const DEFAULT_SMTP_HOST: &str = "localhost";
+const DEFAULT_SMTP_PORT: u16 = 25;
+
+// Plain SMTP, meant for a local relay that handles TLS and auth itself
+pub struct Email {
+    host: String,
+    port: u16,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    dry_run: bool,
+}
+
+impl Email {
+    pub fn new(config: &Config) -> Result<Self, Error> {
+        let port = match config.get("notify.smtp_port") {
+            Some(port) => port
+                .parse()
+                .map_err(|_| Error::config("notify.smtp_port", format!("`{}` is not a port number", port)))?,
+            None => DEFAULT_SMTP_PORT,
+        };
+        let from = config.require("notify.email_from")?;
+        let from = from
+            .parse()
+            .map_err(|e| Error::config("notify.email_from", format!("`{}` is not an address: {}", from, e)))?;
+        let to = config
+            .require("notify.email_to")?
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(|address| {
+                address
+                    .parse()
+                    .map_err(|e| Error::config("notify.email_to", format!("`{}` is not an address: {}", address, e)))
+            })
+            .collect::<Result<Vec<Mailbox>, Error>>()?;
+        Ok(Self {
+            host: config.get("notify.smtp_host").unwrap_or(DEFAULT_SMTP_HOST).to_string(),
+            port,
+            from,
+            to,
+            dry_run: config.dry_run,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for Email {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    async fn send(&self, stand_up: &StandUp<'_>) -> Result<(), Error> {
+        println!("{}", "Sending stand-up by email".yellow());
+        let subject = format!("Stand-up {}", stand_up.context.date);
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(subject.clone())
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+        let message = builder
+            .body(stand_up.text.to_string())
+            .map_err(|e| Error::notify(self.name(), e))?;
+
+        if self.dry_run {
+            let to: Vec<String> = self.to.iter().map(|to| to.to_string()).collect();
+            print_dry_run(
+                &format!("Would email {} through {}:{}", to.join(", "), self.host, self.port),
+                &format!("Subject: {}\n\n{}", subject, stand_up.text),
+            );
+            return Ok(());
+        }
+        AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.host)
+            .port(self.port)
+            .build()
+            .send(message)
+            .await
+            .map_err(|e| Error::notify(self.name(), e))?;
+        println!("{}", "Stand-up emailed".green());
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+pub mod discord;
+pub mod email;
+pub mod teams;
+
+use crate::prelude::*;
+use chrono::NaiveDate;
+use stand_up::StandupContext;
+
+// The rendered stand-up plus what it was rendered from, for targets with their own layout
+pub struct StandUp<'a> {
+    pub text: &'a str,
+    pub context: &'a StandupContext,
+    pub date: NaiveDate,
+    // rendered from standup.template rather than the built-in layout
+    pub custom_template: bool,
+}
+
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn send(&self, stand_up: &StandUp<'_>) -> Result<(), Error>;
+}
+
+pub const TARGETS: [&str; 4] = ["slack", "discord", "teams", "email"];
+
+// notify.targets, a list of TARGETS (comma separated in NOTIFY_TARGETS)
+pub fn configured_targets(config: &Config) -> Result<Vec<String>, Error> {
+    let targets: Vec<String> = config
+        .get("notify.targets")
+        .unwrap_or("slack")
+        .split(',')
+        .map(|target| target.trim().to_lowercase())
+        .filter(|target| !target.is_empty())
+        .collect();
+    match targets.iter().find(|target| !TARGETS.contains(&target.as_str())) {
+        Some(target) => Err(Error::config(
+            "notify.targets",
+            format!("unknown target `{}`, use a comma separated list of {}", target, TARGETS.join(", ")),
+        )),
+        None => Ok(targets),
+    }
+}
+
+// Build every target up front so a missing setting fails before anything is sent
+pub fn from_targets(
+    config: &Config,
+    targets: &[String],
+    slack_options: crate::slack::PostOptions,
+) -> Result<Vec<Box<dyn Notifier>>, Error> {
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+    for target in targets {
+        let notifier: Box<dyn Notifier> = match target.as_str() {
+            "slack" => Box::new(SlackApi::new(config)?.with_options(slack_options.clone())),
+            "discord" => Box::new(discord::DiscordWebhook::new(config)?),
+            "teams" => Box::new(teams::TeamsWebhook::new(config)?),
+            "email" => Box::new(email::Email::new(config)?),
+            _ => continue,
+        };
+        notifiers.push(notifier);
+    }
+    Ok(notifiers)
+}
+
+// Split on line breaks so each chunk stays under `limit` characters
+pub fn chunks(text: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        let line: String = line.chars().take(limit).collect();
+        if !current.is_empty() && current.chars().count() + 1 + line.chars().count() > limit {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(&line);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
@@ -0,0 +1,71 @@
+use crate::prelude::*;
+use super::{Notifier, StandUp};
+use reqwest::Client;
+
+pub struct TeamsWebhook {
+    client: Client,
+    url: String,
+    dry_run: bool,
+}
+
+// Legacy connector card, the format incoming webhooks accept
+#[derive(Debug, Serialize)]
+struct MessageCard {
+    #[serde(rename = "@type")]
+    card_type: String,
+    #[serde(rename = "@context")]
+    context: String,
+    summary: String,
+    title: String,
+    text: String,
+}
+
+impl TeamsWebhook {
+    pub fn new(config: &Config) -> Result<Self, Error> {
+        Ok(Self {
+            client: Client::new(),
+            url: config.require("notify.teams_webhook")?,
+            dry_run: config.dry_run,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for TeamsWebhook {
+    fn name(&self) -> &'static str {
+        "Teams"
+    }
+
+    async fn send(&self, stand_up: &StandUp<'_>) -> Result<(), Error> {
+        println!("{}", "Sending message to Teams".yellow());
+        let title = format!("Stand-up {}", stand_up.context.date);
+        let card = MessageCard {
+            card_type: "MessageCard".to_string(),
+            context: "http://schema.org/extensions".to_string(),
+            summary: title.clone(),
+            title,
+            // Teams markdown needs a blank line for a line break
+            text: stand_up.text.lines().collect::<Vec<&str>>().join("\n\n"),
+        };
+        if self.dry_run {
+            print_dry_run(
+                "Would POST to the Teams webhook",
+                &serde_json::to_string_pretty(&card).map_err(|e| Error::notify(self.name(), e))?,
+            );
+            return Ok(());
+        }
+        let response = self.client
+            .post(&self.url)
+            .json(&card)
+            .send()
+            .await
+            .map_err(|e| Error::notify(self.name(), e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::notify(self.name(), format!("{} {}", status, body)));
+        }
+        println!("{}", "Message sent to Teams".green());
+        Ok(())
+    }
+}
@@ -1,79 +1,143 @@
+pub mod project_cache;
+pub mod query;
 pub mod reading_list;
+pub mod response;
 pub mod stand_up;
+pub mod task_file;
+pub mod template;
 
 
 use crate::prelude::*;
+use chrono::NaiveDate;
 use reqwest::{header::HeaderMap, Client};
 
+const DEFAULT_API_URL: &str = "https://api.notion.com/v1";
+
 pub struct NotionApi {
     client: Client,
     headers: HeaderMap,
     base_url: String,
+    dry_run: bool,
 }
 
 
 impl NotionApi {
-    pub fn new(endpoint: &str) -> Self {
-        let api_key = env::var("NOTION_API_KEY").unwrap();
+    pub fn new(config: &Config, endpoint: &str) -> Result<Self, Error> {
+        let api_key = config.require("notion.api_key")?;
         let mut headers = HeaderMap::new();
-        headers.insert("Authorization", format!("Bearer {}", api_key).parse().unwrap());
+        headers.insert(
+            "Authorization",
+            format!("Bearer {}", api_key)
+                .parse()
+                .map_err(|_| Error::config("notion.api_key", "contains characters not allowed in an HTTP header"))?,
+        );
         headers.insert("Notion-Version", "2022-06-28".parse().unwrap());
         headers.insert("Accept", "application/json".parse().unwrap());
-        Self {
+        Ok(Self {
             client: reqwest::Client::new(),
             headers,
-            base_url: format!("https://api.notion.com/v1/{}",endpoint)
-        }
-        
+            base_url: format!(
+                "{}/{}",
+                config.get("notion.api_url").unwrap_or(DEFAULT_API_URL).trim_end_matches('/'),
+                endpoint
+            ),
+            dry_run: config.dry_run,
+        })
     }
 
-    pub async fn get_articles(&self) -> Result<Vec<reading_list::Article>, Error> {
+    pub async fn get_articles(&self, reading_list_csv: &str) -> Result<Vec<reading_list::Article>, Error> {
         println!("{}", "Getting articles from Notion API".yellow());
 
-        let response = self.client
-        .post(&self.base_url)
-        .headers(self.headers.clone())
-        .send()
-        .await?
-        .json::<reading_list::APIResponse>()
-        .await?;
+        let articles = self
+            .query_all::<reading_list::Article>(serde_json::json!({}))
+            .await?;
+
+        reading_list::update_reading_list(reading_list_csv, &articles, self.dry_run)?;
+        Ok(articles)
+    }
+
+    pub async fn get_article(&self, reading_list_csv: &str) -> Result<String, Error> {
+        self.get_articles(reading_list_csv).await?;
+        reading_list::randomly_choose_article(reading_list_csv, self.dry_run)
+    }
+
+    pub async fn get_projects(&self) -> Result<project_cache::ProjectCache, Error> {
+        println!("{}", "Getting projects from Notion API".yellow());
+        let pages = self
+            .query_all::<project_cache::ProjectPage>(serde_json::json!({}))
+            .await?;
 
-        reading_list::update_reading_list(&response.results)?;
-        Ok(response.results)
+        Ok(project_cache::ProjectCache::from_pages(pages))
     }
 
-    pub async fn get_article(&self) -> Result<String, Error> {
-        self.get_articles().await.unwrap();
-        reading_list::randomly_choose_article()
+    // `blocked_status` is the status option of blocked tasks, databases without one leave it out
+    pub async fn get_tasks(
+        &self,
+        since: NaiveDate,
+        date: NaiveDate,
+        blocked_status: Option<&str>,
+    ) -> Result<stand_up::APIResponse, Error> {
+        let results = self
+            .query_all::<stand_up::Task>(
+                serde_json::to_value(stand_up::Filter::new(since, date, blocked_status)).map_err(Error::notion)?
+            )
+            .await?;
+
+        Ok(stand_up::APIResponse {
+            results,
+            blocked_status: blocked_status.map(str::to_string),
+        })
     }
 
-    pub async fn get_tasks(&self) -> Result<stand_up::APIResponse, Error> {
-        let response = self.client
-        .post(&self.base_url)
-        .json(&stand_up::Filter::new())
-        .headers(self.headers.clone())
-        .send()
-        .await?
-        .json::<stand_up::APIResponse>()
-        .await?;
-
-        Ok(response)
+    pub async fn query_tasks(&self, filter: &stand_up::Filter) -> Result<Vec<stand_up::Task>, Error> {
+        self.query_all::<stand_up::Task>(serde_json::to_value(filter).map_err(Error::notion)?)
+            .await
+    }
+
+    pub async fn get_project(&self) -> Result<project_cache::ProjectPage, Error> {
+        let request = self.client
+            .get(&self.base_url)
+            .headers(self.headers.clone());
+
+        self.send(request).await
+    }
+
+    pub async fn update_task(&self, update: &stand_up::TaskUpdate) -> Result<response::PageResponse, Error> {
+        if self.dry_run {
+            return self.print_dry_run("PATCH", update);
+        }
+        let request = self.client
+            .patch(&self.base_url)
+            .json(update)
+            .headers(self.headers.clone());
+
+        self.send(request).await
     }
 
     pub async fn add_task(
         &self,
         task: String,
-        status: String,
+        status: stand_up::Status,
         database_id: String,
-        project: String
-    ) -> Result<(), Error> {
-        self.client
+        project_id: String
+    ) -> Result<response::PageResponse, Error> {
+        let task_to_add = stand_up::TaskToAdd::new(task, status, database_id, project_id);
+        if self.dry_run {
+            return self.print_dry_run("POST", &task_to_add);
+        }
+        let request = self.client
             .post(&self.base_url)
-            .json(&stand_up::TaskToAdd::new(task, status, database_id, project))
-            .headers(self.headers.clone())
-            .send()
-            .await?;
+            .json(&task_to_add)
+            .headers(self.headers.clone());
+
+        self.send(request).await
+    }
 
-        Ok(())
+    fn print_dry_run<T: Serialize>(&self, method: &str, body: &T) -> Result<response::PageResponse, Error> {
+        print_dry_run(
+            &format!("Would {} {}", method, self.base_url),
+            &serde_json::to_string_pretty(body).map_err(Error::notion)?,
+        );
+        Ok(response::PageResponse { url: "(dry run)".to_string() })
     }
 }
@@ -0,0 +1,163 @@
+use crate::prelude::*;
+use crate::json_file::{load_json, save_json};
+use chrono::prelude::*;
+use std::collections::HashMap;
+
+// Names scoring at least this against the query are offered as suggestions
+const SIMILARITY_THRESHOLD: f64 = 0.75;
+
+// Project names keyed by page id without dashes
+pub type ProjectNames = HashMap<String, String>;
+
+pub fn normalize_id(id: &str) -> String {
+    id.replace('-', "")
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ProjectPage {
+    pub id: String,
+    pub properties: HashMap<String, serde_json::Value>,
+}
+
+impl ProjectPage {
+    // The title property can be named anything, so look it up by type
+    pub fn title(&self) -> Option<String> {
+        self.properties
+            .values()
+            .find(|property| property["type"] == "title")
+            .and_then(|property| property["title"].as_array())
+            .map(|title| {
+                title
+                    .iter()
+                    .filter_map(|text| text["plain_text"].as_str())
+                    .collect::<String>()
+            })
+            .filter(|title| !title.trim().is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ProjectCache {
+    pub synced_at: String,
+    pub projects: Vec<Project>,
+}
+
+impl ProjectCache {
+    pub fn from_pages(pages: Vec<ProjectPage>) -> Self {
+        let mut projects: Vec<Project> = pages
+            .into_iter()
+            .filter_map(|page| page.title().map(|name| Project { id: page.id, name }))
+            .collect();
+        projects.sort_by_key(|project| project.name.to_lowercase());
+        Self {
+            synced_at: Local::now().to_rfc3339(),
+            projects,
+        }
+    }
+
+    pub fn load(file_path: &str) -> Result<Self, Error> {
+        match load_json(file_path) {
+            Ok(Some(cache)) => Ok(cache),
+            Ok(None) => Err(Error::local_file(
+                file_path,
+                "project cache not found, run `automate-me projects sync` first",
+            )),
+            Err(Error::LocalFile { path, message }) => Err(Error::local_file(
+                &path,
+                format!("{}, run `automate-me projects sync` to rebuild it", message),
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, file_path: &str) -> Result<(), Error> {
+        save_json(file_path, self)
+    }
+
+    // Exact name first, then a unique prefix, otherwise suggest close names
+    pub fn resolve(&self, name: &str) -> Result<&Project, Error> {
+        let query = name.trim().to_lowercase();
+        if let Some(project) = self.projects.iter().find(|project| project.name.to_lowercase() == query) {
+            return Ok(project);
+        }
+
+        let prefixed: Vec<&Project> = self
+            .projects
+            .iter()
+            .filter(|project| project.name.to_lowercase().starts_with(&query))
+            .collect();
+        match prefixed.as_slice() {
+            [project] => return Ok(project),
+            [] => {}
+            candidates => {
+                return Err(Error::Validation(format!(
+                    "Project `{}` is ambiguous, did you mean: {}",
+                    name,
+                    names(candidates)
+                )));
+            }
+        }
+
+        let candidates = self.suggestions(&query);
+        if candidates.is_empty() {
+            Err(Error::Validation(format!(
+                "Unknown project `{}`, run `automate-me projects list` to see the synced projects",
+                name
+            )))
+        } else {
+            Err(Error::Validation(format!(
+                "Unknown project `{}`, did you mean: {}",
+                name,
+                names(&candidates)
+            )))
+        }
+    }
+
+    pub fn name_of(&self, id: &str) -> Option<&str> {
+        let id = normalize_id(id);
+        self.projects
+            .iter()
+            .find(|project| normalize_id(&project.id) == id)
+            .map(|project| project.name.as_str())
+    }
+
+    pub fn names(&self) -> ProjectNames {
+        self.projects
+            .iter()
+            .map(|project| (normalize_id(&project.id), project.name.clone()))
+            .collect()
+    }
+
+    fn suggestions(&self, query: &str) -> Vec<&Project> {
+        let mut scored: Vec<(f64, &Project)> = self
+            .projects
+            .iter()
+            .map(|project| {
+                let candidate = project.name.to_lowercase();
+                let score = if candidate.contains(query) {
+                    1.0
+                } else {
+                    strsim::jaro_winkler(query, &candidate)
+                };
+                (score, project)
+            })
+            .filter(|(score, _)| *score >= SIMILARITY_THRESHOLD)
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().take(3).map(|(_, project)| project).collect()
+    }
+}
+
+fn names(projects: &[&Project]) -> String {
+    projects
+        .iter()
+        .map(|project| project.name.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
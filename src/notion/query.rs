@@ -0,0 +1,137 @@
+use crate::prelude::*;
+use futures::stream::{self, Stream, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+// Notion never returns more than 100 results per page
+pub const PAGE_SIZE: u32 = 100;
+
+#[derive(Deserialize, Debug)]
+pub struct QueryPage<T> {
+    pub results: Vec<T>,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+enum Cursor {
+    Start,
+    Next(String),
+    Done,
+}
+
+impl NotionApi {
+    // Fetch a single page of `databases/{id}/query`, merging the cursor into the body
+    pub async fn query_page<T: DeserializeOwned>(
+        &self,
+        body: &Value,
+        start_cursor: Option<String>,
+    ) -> Result<QueryPage<T>, Error> {
+        let mut body = match body {
+            Value::Object(map) => map.clone(),
+            Value::Null => Map::new(),
+            _ => return Err(Error::notion("query body must be a JSON object")),
+        };
+        body.insert("page_size".to_string(), Value::from(PAGE_SIZE));
+        if let Some(cursor) = start_cursor {
+            body.insert("start_cursor".to_string(), Value::String(cursor));
+        }
+
+        let request = self.client
+            .post(&self.base_url)
+            .json(&body)
+            .headers(self.headers.clone());
+
+        self.send(request).await
+    }
+
+    // Lazily walk every page of the query, yielding results one at a time
+    pub fn query_stream<'a, T: DeserializeOwned + 'a>(
+        &'a self,
+        body: Value,
+    ) -> impl Stream<Item = Result<T, Error>> + 'a {
+        stream::try_unfold(Cursor::Start, move |cursor| {
+            let body = body.clone();
+            async move {
+                let start_cursor = match cursor {
+                    Cursor::Start => None,
+                    Cursor::Next(next_cursor) => Some(next_cursor),
+                    Cursor::Done => return Ok::<_, Error>(None),
+                };
+                let page = self.query_page::<T>(&body, start_cursor).await?;
+                let next = match (page.has_more, page.next_cursor) {
+                    (true, Some(next_cursor)) => Cursor::Next(next_cursor),
+                    _ => Cursor::Done,
+                };
+                Ok(Some((stream::iter(page.results.into_iter().map(Ok::<T, Error>)), next)))
+            }
+        })
+        .try_flatten()
+    }
+
+    // Follow `next_cursor` until the end and return the whole result set
+    pub async fn query_all<T: DeserializeOwned>(&self, body: Value) -> Result<Vec<T>, Error> {
+        self.query_stream(body).try_collect().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+    use serde_json::json;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[derive(Deserialize, Debug)]
+    struct Page {
+        id: String,
+    }
+
+    fn query_page(ids: &[&str], next_cursor: Option<&str>) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "results": ids.iter().map(|id| json!({ "id": id })).collect::<Vec<Value>>(),
+            "has_more": next_cursor.is_some(),
+            "next_cursor": next_cursor
+        }))
+    }
+
+    #[tokio::test]
+    async fn query_all_follows_next_cursor_until_the_last_page() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/databases/db/query"))
+            .and(body_partial_json(json!({ "start_cursor": "page-2" })))
+            .respond_with(query_page(&["third"], None))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/databases/db/query"))
+            .respond_with(query_page(&["first", "second"], Some("page-2")))
+            .mount(&server)
+            .await;
+        let api = NotionApi {
+            client: reqwest::Client::new(),
+            headers: HeaderMap::new(),
+            base_url: format!("{}/databases/db/query", server.uri()),
+            dry_run: false,
+        };
+
+        let pages = api
+            .query_all::<Page>(json!({ "filter": { "property": "Done" } }))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = pages.iter().map(|page| page.id.as_str()).collect();
+        assert_eq!(ids, ["first", "second", "third"]);
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+        let first: Value = requests[0].body_json().unwrap();
+        assert_eq!(first["page_size"], PAGE_SIZE);
+        assert_eq!(first["filter"]["property"], "Done");
+        assert!(first.get("start_cursor").is_none());
+        let second: Value = requests[1].body_json().unwrap();
+        assert_eq!(second["start_cursor"], "page-2");
+        assert_eq!(second["filter"]["property"], "Done");
+    }
+}
@@ -6,11 +6,6 @@ use std::io::BufWriter;
 use std::fs::File;
 
 
-#[derive(Deserialize, Debug)]
-pub struct APIResponse {
-    pub results: Vec<Article>,
-}
-
 #[derive(Deserialize, Debug, Clone)]
 pub struct Article {
     pub id: String,
@@ -31,7 +26,7 @@ pub struct ReadingInfo {
     pub read_it: bool,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct ReadingList {
     pub id: String,
     pub url: String,
@@ -39,29 +34,52 @@ pub struct ReadingList {
     pub priority: i32,
 }
 
-fn write_reading_list_to_file(file_path: &str, reading_lists: &[ReadingList]) -> Result<(), Error> {
+fn write_reading_list_to_file(file_path: &str, reading_lists: &[ReadingList], dry_run: bool) -> Result<(), Error> {
+    if dry_run {
+        let existing = read_reading_list(file_path)?;
+        let changed: Vec<String> = reading_lists
+            .iter()
+            .filter(|record| !existing.contains(record))
+            .map(|record| format!(" • {},{},{},{}", record.id, record.url, record.did_i_read_it, record.priority))
+            .collect();
+        print_dry_run(
+            &format!("Would rewrite {} with {} changed rows", file_path, changed.len()),
+            &changed.join("\n"),
+        );
+        return Ok(());
+    }
     let temp_file_path = "temp_reading_list.csv";
+    let temp_file_error = |e| Error::local_file(temp_file_path, e);
 
-    let mut wtr = WriterBuilder::new().from_writer(BufWriter::new(File::create(&temp_file_path)?));
+    let mut wtr = WriterBuilder::new().from_writer(BufWriter::new(
+        File::create(temp_file_path).map_err(temp_file_error)?
+    ));
 
     for record in reading_lists {
-        wtr.serialize(record)?;
+        wtr.serialize(record).map_err(|e| Error::local_file(temp_file_path, e))?;
     }
 
-    wtr.flush()?;
+    wtr.flush().map_err(temp_file_error)?;
 
-    fs::remove_file(file_path)?;
-    fs::rename(&temp_file_path, file_path)?;
+    fs::remove_file(file_path).map_err(|e| Error::local_file(file_path, e))?;
+    fs::rename(temp_file_path, file_path).map_err(|e| Error::local_file(file_path, e))?;
 
     Ok(())
 }
 
+fn read_reading_list(file_path: &str) -> Result<Vec<ReadingList>, Error> {
+    let mut rdr = ReaderBuilder::new()
+        .from_path(file_path)
+        .map_err(|e| Error::local_file(file_path, e))?;
+
+    rdr.deserialize()
+        .collect::<Result<Vec<ReadingList>, csv::Error>>()
+        .map_err(|e| Error::local_file(file_path, format!("{}, expected columns id,url,did_i_read_it,priority", e)))
+}
 
-pub fn update_reading_list(list_of_articles: &Vec<Article>) -> Result<(), Error> {
-    let file_path = env::var("READING_LIST_CSV").unwrap();
-    let mut rdr = ReaderBuilder::new().from_path(&file_path)?;
 
-    let mut existing_data: Vec<ReadingList> = rdr.deserialize().map(|r| r.unwrap()).collect();
+pub fn update_reading_list(file_path: &str, list_of_articles: &Vec<Article>, dry_run: bool) -> Result<(), Error> {
+    let mut existing_data = read_reading_list(file_path)?;
     let existing_ids: HashMap<String, usize> = existing_data.iter().enumerate().map(|(i, r)| (r.id.clone(), i)).collect();
 
     for article in list_of_articles {
@@ -91,18 +109,18 @@ pub fn update_reading_list(list_of_articles: &Vec<Article>) -> Result<(), Error>
         }
     }
 
-    write_reading_list_to_file(&file_path, &existing_data)
+    write_reading_list_to_file(file_path, &existing_data, dry_run)
 }
 
-pub fn randomly_choose_article() -> Result<String, Error> {
+pub fn randomly_choose_article(file_path: &str, dry_run: bool) -> Result<String, Error> {
     println!("{}", "Choosing article".yellow());
-    let file_path = env::var("READING_LIST_CSV").unwrap();
-    let mut rdr = ReaderBuilder::new().from_path(&file_path)?;
-
-    let mut reading_lists: Vec<ReadingList> = rdr.deserialize().map(|r| r.unwrap()).collect();
+    let mut reading_lists = read_reading_list(file_path)?;
     let priorities: Vec<i32> = reading_lists.iter().map(|r| r.priority).collect();
 
-    let dist = WeightedIndex::new(&priorities).unwrap();
+    let dist = WeightedIndex::new(&priorities).map_err(|_| Error::Validation(format!(
+        "No article to choose from, {} is empty or every priority is 0",
+        file_path
+    )))?;
     let mut rng = thread_rng();
     let chosen_index = dist.sample(&mut rng);
     let chosen_id = reading_lists[chosen_index].id.clone();
@@ -115,7 +133,7 @@ pub fn randomly_choose_article() -> Result<String, Error> {
         }
     }
 
-    write_reading_list_to_file(&file_path, &reading_lists)?;
+    write_reading_list_to_file(file_path, &reading_lists, dry_run)?;
 
     Ok(chosen_url)
 }
@@ -0,0 +1,96 @@
+use crate::prelude::*;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+const MAX_RETRIES: u32 = 4;
+const BACKOFF_BASE_MS: u64 = 500;
+
+// Notion's error envelope: {"object": "error", "status": 400, "code": "...", "message": "..."}
+#[derive(Deserialize, Debug)]
+pub struct ErrorResponse {
+    pub object: String,
+    pub status: u16,
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PageResponse {
+    pub url: String,
+}
+
+impl NotionApi {
+    // Send a request, retrying on 429 and 5xx, and decode either the body or Notion's error
+    pub async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, Error> {
+        let response = self.send_with_retry(request).await?;
+        let status = response.status();
+        let body = response.text().await.map_err(Error::notion)?;
+
+        if !status.is_success() {
+            return Err(error_from_body(status, &body));
+        }
+        serde_json::from_str::<T>(&body).map_err(|e| match serde_json::from_str::<ErrorResponse>(&body) {
+            Ok(error) if error.object == "error" => error.into(),
+            _ => Error::notion(format!("unexpected response: {}", e)),
+        })
+    }
+
+    async fn send_with_retry(&self, request: RequestBuilder) -> Result<Response, Error> {
+        let mut attempt = 0;
+        loop {
+            let retry = request
+                .try_clone()
+                .ok_or_else(|| Error::notion("request body can't be retried"))?;
+            let response = retry.send().await.map_err(Error::notion)?;
+            let status = response.status();
+
+            let wait = if status == StatusCode::TOO_MANY_REQUESTS {
+                retry_after(&response).unwrap_or_else(|| backoff(attempt))
+            } else if status.is_server_error() {
+                backoff(attempt)
+            } else {
+                return Ok(response);
+            };
+
+            if attempt >= MAX_RETRIES {
+                return Ok(response);
+            }
+            println!("{}", format!("Notion returned {}, retrying in {:?}", status, wait).yellow());
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+}
+
+impl From<ErrorResponse> for Error {
+    fn from(error: ErrorResponse) -> Self {
+        Error::NotionResponse {
+            status: error.status,
+            code: error.code,
+            message: error.message,
+        }
+    }
+}
+
+fn error_from_body(status: StatusCode, body: &str) -> Error {
+    match serde_json::from_str::<ErrorResponse>(body) {
+        Ok(error) => error.into(),
+        Err(_) => Error::notion(format!("{} {}", status, body)),
+    }
+}
+
+pub fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get("Retry-After")?
+        .to_str()
+        .ok()?
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+pub fn backoff(attempt: u32) -> Duration {
+    Duration::from_millis(BACKOFF_BASE_MS * 2u64.pow(attempt))
+}
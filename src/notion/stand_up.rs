@@ -1,13 +1,13 @@
 use crate::prelude::*;
+use super::project_cache::{normalize_id, ProjectNames};
 use chrono::prelude::*;
-use std::collections::HashMap;
-use std::str::FromStr;
-use std::fs::File;
-use std::io::Read;
+use std::fmt;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct APIResponse {
     pub results: Vec<Task>,
+    #[serde(skip)]
+    pub blocked_status: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -33,6 +33,21 @@ pub enum FilterCondition {
     Or(Vec<StatusCondition>),
     #[serde(untagged)]
     LastEditedTime(LastEditedTimeCondition),
+    #[serde(untagged)]
+    Status(StatusCondition),
+    #[serde(untagged)]
+    Relation(RelationCondition),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RelationCondition {
+    pub property: String,
+    pub relation: RelationContains,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RelationContains {
+    pub contains: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -46,34 +61,67 @@ pub struct  StatusEquals {
     pub equals: Status
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum Status {
     #[serde(rename = "In progress")]
     InProgress,
     Done,
     #[serde(rename = "To Do")]
     ToDo,
+    // Any other option of the database, e.g. "Archived", these tasks are left out
+    // unless it's the one named by standup.blocked_status
+    #[serde(untagged)]
+    Other(String),
 }
 
-impl FromStr for Status {
-    type Err = ();
+impl Status {
+    // The statuses a task can be given, the blocked one only when standup.blocked_status names it
+    pub fn all(blocked_status: Option<&str>) -> Vec<Status> {
+        let mut statuses = vec![Status::ToDo, Status::InProgress, Status::Done];
+        statuses.extend(blocked_status.map(|blocked| Status::Other(blocked.to_string())));
+        statuses
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "in progress" => Ok(Status::InProgress),
-            "done" => Ok(Status::Done),
-            "to do" => Ok(Status::ToDo),
-            _ => Err(()),
+    // Case insensitive, "blocked" stands for the standup.blocked_status option whatever it is called
+    pub fn parse(s: &str, blocked_status: Option<&str>) -> Result<Status, Error> {
+        let name = s.trim().to_lowercase();
+        if let Some(status) = Status::all(blocked_status).into_iter().find(|status| {
+            status.to_string().to_lowercase() == name || (name == "blocked" && matches!(status, Status::Other(_)))
+        }) {
+            return Ok(status);
+        }
+        let mut possible = vec!["\"to do\"", "\"in progress\"", "\"done\""];
+        if blocked_status.is_some() {
+            possible.push("\"blocked\"");
+        }
+        Err(Error::Validation(format!(
+            "Unknown status `{}`, possible values: {}{}",
+            s,
+            possible.join(", "),
+            if blocked_status.is_none() && name == "blocked" {
+                ", set standup.blocked_status to the Notion status option of blocked tasks"
+            } else {
+                ""
+            }
+        )))
+    }
+
+    // To Do, In progress, Done and the blocked status, the rest are left out of lists and lookups
+    pub fn is_tracked(&self, blocked_status: Option<&str>) -> bool {
+        match self {
+            Status::Other(name) => blocked_status.is_some_and(|blocked| blocked.eq_ignore_ascii_case(name)),
+            _ => true,
         }
     }
 }
 
-impl ToString for Status {
-    fn to_string(&self) -> String {
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Status::InProgress => String::from("In progress"),
-            Status::Done => String::from("Done"),
-            Status::ToDo => String::from("To Do"),
+            Status::InProgress => write!(f, "In progress"),
+            Status::Done => write!(f, "Done"),
+            Status::ToDo => write!(f, "To Do"),
+            Status::Other(name) => write!(f, "{}", name),
         }
     }
 }
@@ -81,20 +129,63 @@ impl ToString for Status {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct LastEditedTimeCondition {
     pub property: String,
-    pub last_edited_time: OnOrAfter,
+    pub last_edited_time: DateCondition,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-pub struct OnOrAfter {
-    #[serde(rename = "on_or_after")]
-    pub date: String,
+#[serde(rename_all = "snake_case")]
+pub enum DateCondition {
+    OnOrAfter(String),
+    OnOrBefore(String),
+}
+
+impl LastEditedTimeCondition {
+    fn new(condition: DateCondition) -> Self {
+        Self {
+            property: String::from("Last edited time"),
+            last_edited_time: condition,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Task {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub last_edited_time: String,
     pub properties: Properties
 }
 
+impl Task {
+    pub fn name(&self) -> String {
+        self.properties
+            .name
+            .title
+            .iter()
+            .map(|title| title.plain_text.as_str())
+            .collect()
+    }
+
+    pub fn status(&self) -> Status {
+        self.properties.status.status.name.clone()
+    }
+
+    pub fn project_ids(&self) -> Vec<&str> {
+        self.properties
+            .project
+            .relation
+            .iter()
+            .map(|relation| relation.id.as_str())
+            .collect()
+    }
+
+    // First 8 characters of the page id, enough to tell recent tasks apart
+    pub fn short_id(&self) -> String {
+        self.id.replace('-', "").chars().take(8).collect()
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Properties {
     #[serde(rename = "Name")]
@@ -156,8 +247,7 @@ pub struct Relation {
 }
 
 impl TaskToAdd {
-    pub fn new(task: String, status: String, database_id: String, project: String) -> Self {
-        let status = Status::from_str(&status).unwrap();
+    pub fn new(task: String, status: Status, database_id: String, project_id: String) -> Self {
         Self {
             properties: Properties {
                 name: Name {
@@ -179,7 +269,7 @@ impl TaskToAdd {
                     relation_type: String::from("relation"),
                     relation: vec![
                         Relation {
-                            id: project_to_id(&project).unwrap() 
+                            id: project_id
                         }
                     ],
                     has_more: false
@@ -192,34 +282,10 @@ impl TaskToAdd {
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct ProjectMapping {
-    mapping: HashMap<String, String>,
-}
-
-fn read_project_mapping(file_path: &str) -> Result<ProjectMapping, Box<dyn std::error::Error>> {
-    let mut file = File::open(file_path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-
-    let mapping: ProjectMapping = serde_json::from_str(&contents)?;
-
-    Ok(mapping)
-}
-
-
-fn project_to_id(project: &str) -> Option<String> {
-    let file_path = env::var("PROJECT_MAPPING_JSON").unwrap();
-    if let Ok(project_mapping) = read_project_mapping(&file_path) {
-        project_mapping.mapping.get(project.to_lowercase().as_str()).cloned()
-    } else {
-        None
-    }
-}
-
 impl Filter {
-    pub fn new() -> Self {
-        Self {
+    // Tasks edited from `since` up to the stand-up `date`, the upper bound only matters for past dates
+    pub fn new(since: NaiveDate, date: NaiveDate, blocked_status: Option<&str>) -> Self {
+        let mut filter = Self {
             filter: FilterDetails {
                 and: vec![
                     FilterCondition::Or(vec![
@@ -242,12 +308,9 @@ impl Filter {
                             }
                         },
                     ]),
-                    FilterCondition::LastEditedTime(LastEditedTimeCondition {
-                        property: String::from("Last edited time"),
-                        last_edited_time: OnOrAfter {
-                            date: Local::now().format("%Y-%m-%d").to_string(),
-                        },
-                    }),
+                    FilterCondition::LastEditedTime(LastEditedTimeCondition::new(
+                        DateCondition::OnOrAfter(since.format("%Y-%m-%d").to_string())
+                    )),
                 ],
             },
             sorts: vec![
@@ -256,29 +319,139 @@ impl Filter {
                     direction: String::from("ascending") 
                 }
             ],
+        };
+        if let (Some(blocked_status), Some(FilterCondition::Or(statuses))) = (blocked_status, filter.filter.and.first_mut()) {
+            statuses.push(StatusCondition {
+                property: String::from("Status"),
+                status: StatusEquals {
+                    equals: Status::Other(blocked_status.to_string()),
+                }
+            });
+        }
+        if date < Local::now().date_naive() {
+            filter.filter.and.push(FilterCondition::LastEditedTime(LastEditedTimeCondition::new(
+                DateCondition::OnOrBefore(date.format("%Y-%m-%d").to_string())
+            )));
+        }
+        filter
+    }
+}
+
+impl Filter {
+    // Filter for `tasks list`, every condition is optional
+    pub fn for_tasks(
+        status: Option<Status>,
+        project_id: Option<String>,
+        since: NaiveDate,
+    ) -> Self {
+        let mut and = vec![FilterCondition::LastEditedTime(LastEditedTimeCondition::new(
+            DateCondition::OnOrAfter(since.format("%Y-%m-%d").to_string())
+        ))];
+        if let Some(status) = status {
+            and.push(FilterCondition::Status(StatusCondition {
+                property: String::from("Status"),
+                status: StatusEquals {
+                    equals: status,
+                },
+            }));
+        }
+        if let Some(project_id) = project_id {
+            and.push(FilterCondition::Relation(RelationCondition {
+                property: String::from("Projects"),
+                relation: RelationContains {
+                    contains: project_id,
+                },
+            }));
+        }
+        Self {
+            filter: FilterDetails { and },
+            sorts: vec![
+                Sort {
+                    property: String::from("Last edited time"),
+                    direction: String::from("descending")
+                }
+            ],
+        }
+    }
+}
+
+// Body of `PATCH pages/{id}`, only the properties that are set get changed
+#[derive(Debug, Serialize)]
+pub struct TaskUpdate {
+    properties: UpdateProperties,
+}
+
+#[derive(Debug, Serialize)]
+struct UpdateProperties {
+    #[serde(rename = "Name", skip_serializing_if = "Option::is_none")]
+    name: Option<Name>,
+    #[serde(rename = "Status", skip_serializing_if = "Option::is_none")]
+    status: Option<StatusInfo>,
+}
+
+impl TaskUpdate {
+    pub fn status(status: Status) -> Self {
+        Self {
+            properties: UpdateProperties {
+                name: None,
+                status: Some(StatusInfo {
+                    status: TaskStatus {
+                        name: status
+                    }
+                }),
+            }
+        }
+    }
+
+    pub fn rename(title: String) -> Self {
+        Self {
+            properties: UpdateProperties {
+                name: Some(Name {
+                    title: vec![
+                        Title {
+                            plain_text: title.clone(),
+                            text: TitleTask {
+                                content: title
+                            }
+                        }
+                    ]
+                }),
+                status: None,
+            }
         }
     }
 }
 
 impl APIResponse {
-    fn classify_tasks(&self) -> ClassifiedTasks {
+    fn classify_tasks(&self, projects: &ProjectNames) -> ClassifiedTasks {
         let mut today = Vec::new();
         let mut tomorrow = Vec::new();
+        let mut blockers = Vec::new();
         for task in self.results.iter() {
-            let name = task.properties.name.title[0].plain_text.clone();
-            let status = &task.properties.status.status.name;
-
+            let status = task.status();
+            let standup_task = StandupTask {
+                name: task.name(),
+                status: status.to_string(),
+                wip: status == Status::InProgress,
+                projects: task
+                    .project_ids()
+                    .iter()
+                    .filter_map(|id| projects.get(&normalize_id(id)).cloned())
+                    .collect(),
+            };
+
+            if self.blocked_status.as_deref().is_some_and(|blocked| blocked.eq_ignore_ascii_case(&status.to_string())) {
+                blockers.push(standup_task);
+                continue;
+            }
             match status {
-                Status::Done => {
-                    today.push(name);
+                Status::Done | Status::InProgress => {
+                    today.push(standup_task);
                 },
-                Status::InProgress => {
-                    let wip = format!("WIP {}", name);
-                    today.push(wip);
+                Status::ToDo => {
+                    tomorrow.push(standup_task);
                 },
-                _ => {
-                    tomorrow.push(name);
-                }
+                Status::Other(_) => {}
             }
 
         }
@@ -286,42 +459,174 @@ impl APIResponse {
         ClassifiedTasks {
             today,
             tomorrow,
+            blockers,
         }
     }
 
-    fn format_tasks(&self, tasks: Vec<String>) -> String {
+    fn format_tasks(&self, tasks: Vec<StandupTask>) -> String {
         tasks
             .iter()
-            .map(|task| format!(" • {}", task))
+            .map(|task| format!(" • {}", task.label()))
             .collect::<Vec<String>>()
             .join("\n")
     }
-    pub fn tasks_for_standup(&self) -> String {
-        let classified_tasks = self.classify_tasks();     
-        let today = formatted_today();
-        let today_tasks_str: String = self.format_tasks(classified_tasks.today);
-        let tomorrow_tasks_str: String = self.format_tasks(classified_tasks.tomorrow);
-        let result = format!(
-            "Stand-up {}\nToday\n{}\nTomorrow\n{}\nBlocker\n • None",
-            today, today_tasks_str, tomorrow_tasks_str
-        );
-
-        result
+
+    // Every project id the tasks relate to, to look up the ones missing from the cache
+    pub fn project_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .results
+            .iter()
+            .flat_map(|task| task.project_ids())
+            .map(normalize_id)
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
     }
 
-    pub fn tasks_for_timelog(&self) -> String {
-        self.format_tasks(self.classify_tasks().today)
+    // Everything a stand-up template can use, `extra_blockers` come from --blocker
+    pub fn standup_context(
+        &self,
+        date: NaiveDate,
+        projects: &ProjectNames,
+        extra_blockers: &[String],
+        grouped: bool,
+    ) -> StandupContext {
+        let classified_tasks = self.classify_tasks(projects);
+        let mut project_names: Vec<String> = classified_tasks
+            .today
+            .iter()
+            .chain(classified_tasks.tomorrow.iter())
+            .chain(classified_tasks.blockers.iter())
+            .flat_map(|task| task.projects.clone())
+            .collect();
+        project_names.sort();
+        project_names.dedup();
+
+        StandupContext {
+            date: formatted_date(date),
+            iso_date: date.format("%Y-%m-%d").to_string(),
+            weekday: date.format("%A").to_string(),
+            today_groups: group_by_project(&classified_tasks.today, grouped),
+            tomorrow_groups: group_by_project(&classified_tasks.tomorrow, grouped),
+            today: classified_tasks.today,
+            tomorrow: classified_tasks.tomorrow,
+            blockers: classified_tasks
+                .blockers
+                .into_iter()
+                .map(|task| task.name)
+                .chain(extra_blockers.iter().cloned())
+                .collect(),
+            projects: project_names,
+        }
+    }
+
+    pub fn tasks_for_timelog(&self, projects: &ProjectNames, grouped: bool) -> String {
+        group_by_project(&self.classify_tasks(projects).today, grouped)
+            .into_iter()
+            .map(|group| {
+                let tasks = self.format_tasks(group.tasks);
+                match group.project {
+                    Some(project) => format!("{}\n{}", project, tasks),
+                    None => tasks,
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    // Projects of the tasks worked on, for the timelog's projects column
+    pub fn projects_for_timelog(&self, projects: &ProjectNames) -> Vec<String> {
+        let mut project_names: Vec<String> = self
+            .classify_tasks(projects)
+            .today
+            .into_iter()
+            .flat_map(|task| task.projects)
+            .collect();
+        project_names.sort();
+        project_names.dedup();
+        project_names
     }
 }
 
 struct ClassifiedTasks {
-    today: Vec<String>,
-    tomorrow: Vec<String>,
+    today: Vec<StandupTask>,
+    tomorrow: Vec<StandupTask>,
+    blockers: Vec<StandupTask>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StandupTask {
+    pub name: String,
+    pub status: String,
+    pub wip: bool,
+    pub projects: Vec<String>,
+}
+
+impl StandupTask {
+    pub fn label(&self) -> String {
+        if self.wip {
+            format!("WIP {}", self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+// Tasks under one project heading, `project` is None when grouping is off
+#[derive(Debug, Serialize)]
+pub struct TaskGroup {
+    pub project: Option<String>,
+    pub tasks: Vec<StandupTask>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StandupContext {
+    pub date: String,
+    pub iso_date: String,
+    pub weekday: String,
+    pub today_groups: Vec<TaskGroup>,
+    pub tomorrow_groups: Vec<TaskGroup>,
+    pub today: Vec<StandupTask>,
+    pub tomorrow: Vec<StandupTask>,
+    pub blockers: Vec<String>,
+    pub projects: Vec<String>,
+}
+
+const NO_PROJECT: &str = "Other";
+
+// Keep the order projects first show up in, tasks without a project go last
+fn group_by_project(tasks: &[StandupTask], grouped: bool) -> Vec<TaskGroup> {
+    let no_projects = tasks.iter().all(|task| task.projects.is_empty());
+    if !grouped || no_projects {
+        return vec![TaskGroup { project: None, tasks: tasks.to_vec() }];
+    }
+    let mut groups: Vec<TaskGroup> = Vec::new();
+    let mut other = Vec::new();
+    for task in tasks {
+        match task.projects.first() {
+            Some(project) => match groups.iter_mut().find(|group| group.project.as_ref() == Some(project)) {
+                Some(group) => group.tasks.push(task.clone()),
+                None => groups.push(TaskGroup {
+                    project: Some(project.clone()),
+                    tasks: vec![task.clone()],
+                }),
+            },
+            None => other.push(task.clone()),
+        }
+    }
+    if !other.is_empty() {
+        groups.push(TaskGroup {
+            project: Some(NO_PROJECT.to_string()),
+            tasks: other,
+        });
+    }
+    groups
 }
 
-fn formatted_today() -> String {
-    let today = Local::now();
-    let day = today.day();
+// `Oct 18th` style date used in the stand-up header
+fn formatted_date(date: NaiveDate) -> String {
+    let day = date.day();
 
     let day_suffix = match day {
         1 | 21 | 31 => "st",
@@ -330,5 +635,5 @@ fn formatted_today() -> String {
         _ => "th",
     };
 
-    format!("{}{}{}", today.format("%b"), today.format(" %d"), day_suffix)
+    format!("{}{}{}", date.format("%b"), date.format(" %d"), day_suffix)
 }
@@ -0,0 +1,138 @@
+use crate::prelude::*;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskFileFormat {
+    Csv,
+    Markdown,
+}
+
+// One task read from a file, `line` is kept for the summary
+#[derive(Debug)]
+pub struct TaskRow {
+    pub line: usize,
+    pub title: String,
+    pub status: String,
+    pub project: Option<String>,
+}
+
+// A row that couldn't be read, reported with the tasks that failed to send
+#[derive(Debug)]
+pub struct FailedRow {
+    pub line: usize,
+    pub title: String,
+    pub error: Error,
+}
+
+#[derive(Debug, Deserialize)]
+struct CsvRow {
+    task: String,
+    status: Option<String>,
+    project: Option<String>,
+}
+
+// Read tasks from a csv/markdown file, or stdin when the path is "-"
+pub fn read_task_file(path: &str, default_status: &str) -> Result<Vec<Result<TaskRow, FailedRow>>, Error> {
+    let contents = if path == "-" {
+        let mut contents = String::new();
+        std::io::stdin()
+            .read_to_string(&mut contents)
+            .map_err(|e| Error::local_file("stdin", e))?;
+        contents
+    } else {
+        fs::read_to_string(path).map_err(|e| Error::local_file(path, e))?
+    };
+
+    match detect_format(path, &contents) {
+        TaskFileFormat::Csv => parse_csv(path, &contents, default_status),
+        TaskFileFormat::Markdown => Ok(parse_markdown(&contents).into_iter().map(Ok).collect()),
+    }
+}
+
+fn detect_format(path: &str, contents: &str) -> TaskFileFormat {
+    match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => TaskFileFormat::Csv,
+        Some("md") | Some("markdown") => TaskFileFormat::Markdown,
+        _ => {
+            let first_line = contents.lines().map(str::trim).find(|line| !line.is_empty());
+            match first_line {
+                Some(line) if line.starts_with('#') || checkbox(line).is_some() => TaskFileFormat::Markdown,
+                _ => TaskFileFormat::Csv,
+            }
+        }
+    }
+}
+
+// Columns: task,status,project with status and project optional, a bad row doesn't stop the others
+fn parse_csv(path: &str, contents: &str, default_status: &str) -> Result<Vec<Result<TaskRow, FailedRow>>, Error> {
+    let mut rdr = ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(contents.as_bytes());
+    let headers = rdr.headers().map_err(|e| Error::local_file(path, e))?.clone();
+    let mut rows = Vec::new();
+    for record in rdr.records() {
+        let record = record.map_err(|e| Error::local_file(path, e))?;
+        // quoted fields can span lines, so the line comes from the reader
+        let line = record.position().map_or(0, |position| position.line() as usize);
+        let row = record
+            .deserialize::<CsvRow>(Some(&headers))
+            .map_err(|e| format!("{}, expected columns task,status,project", e))
+            .and_then(|row| match row.task.is_empty() {
+                true => Err("the task column is empty".to_string()),
+                false => Ok(row),
+            });
+        rows.push(match row {
+            Ok(row) => Ok(TaskRow {
+                line,
+                title: row.task,
+                status: row.status.filter(|status| !status.is_empty()).unwrap_or(default_status.to_string()),
+                project: row.project.filter(|project| !project.is_empty()),
+            }),
+            Err(message) => Err(FailedRow {
+                line,
+                title: record.get(0).unwrap_or_default().to_string(),
+                error: Error::Validation(message),
+            }),
+        });
+    }
+    Ok(rows)
+}
+
+// `- [x] task` is done, `- [ ] task` is to do and headings set the project of what follows
+fn parse_markdown(contents: &str) -> Vec<TaskRow> {
+    let mut project = None;
+    let mut rows = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            let heading = line.trim_start_matches('#').trim();
+            project = Some(heading.to_string()).filter(|heading| !heading.is_empty());
+        } else if let Some((done, title)) = checkbox(line) {
+            rows.push(TaskRow {
+                line: index + 1,
+                title: title.to_string(),
+                status: if done { stand_up::Status::Done } else { stand_up::Status::ToDo }.to_string(),
+                project: project.clone(),
+            });
+        }
+    }
+    rows
+}
+
+fn checkbox(line: &str) -> Option<(bool, &str)> {
+    let item = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))?
+        .trim_start();
+    let (done, title) = if let Some(title) = item.strip_prefix("[ ]") {
+        (false, title)
+    } else if let Some(title) = item.strip_prefix("[x]").or_else(|| item.strip_prefix("[X]")) {
+        (true, title)
+    } else {
+        return None;
+    };
+    Some((done, title.trim())).filter(|(_, title)| !title.is_empty())
+}
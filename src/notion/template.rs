@@ -0,0 +1,41 @@
+use crate::prelude::*;
+use super::stand_up::StandupContext;
+use handlebars::{no_escape, Handlebars, Template};
+use std::fs;
+
+// The layout we always used, kept as the template when none is configured
+pub const DEFAULT_TEMPLATE: &str = "\
+Stand-up {{date}}
+Today
+{{#each today_groups}}{{#if project}}*{{project}}*
+{{/if}}{{#each tasks}} • {{#if wip}}WIP {{/if}}{{name}}
+{{/each}}{{/each}}Tomorrow
+{{#each tomorrow_groups}}{{#if project}}*{{project}}*
+{{/if}}{{#each tasks}} • {{name}}
+{{/each}}{{/each}}Blocker
+{{#each blockers}} • {{this}}
+{{else}} • None
+{{/each}}";
+
+// standup.template from the config, or the built-in layout, checked before any request
+pub fn load(config: &Config) -> Result<String, Error> {
+    match config.get("standup.template") {
+        Some(file_path) => {
+            let template = fs::read_to_string(file_path).map_err(|e| Error::local_file(file_path, e))?;
+            Template::compile(&template)
+                .map_err(|e| Error::Validation(format!("Stand-up template {} is invalid: {}", file_path, e)))?;
+            Ok(template)
+        }
+        None => Ok(DEFAULT_TEMPLATE.to_string()),
+    }
+}
+
+pub fn render(template: &str, context: &StandupContext) -> Result<String, Error> {
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(no_escape);
+    handlebars.set_strict_mode(true);
+    let rendered = handlebars
+        .render_template(template, context)
+        .map_err(|e| Error::Validation(format!("Stand-up template failed to render: {}", e)))?;
+    Ok(rendered.trim_end().to_string())
+}
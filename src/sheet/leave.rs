@@ -0,0 +1,50 @@
+use crate::prelude::*;
+use crate::json_file::{load_json, save_json};
+use crate::calendar::{DayOff, DayOffKind};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+// Leave taken with `timelog leave`, the reason keyed by the YYYY-MM-DD date
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct LeaveLog {
+    #[serde(default)]
+    pub days: BTreeMap<String, Option<String>>,
+}
+
+impl LeaveLog {
+    // A missing file means no leave was taken yet
+    pub fn load(file_path: &str) -> Result<Self, Error> {
+        Ok(load_json(file_path)?.unwrap_or_default())
+    }
+
+    pub fn add(&mut self, date: NaiveDate, reason: Option<String>) -> DayOff {
+        self.days.insert(date.format("%Y-%m-%d").to_string(), reason.clone());
+        day_off(date, reason)
+    }
+
+    pub fn days_off(&self, file_path: &str) -> Result<Vec<DayOff>, Error> {
+        self.days
+            .iter()
+            .map(|(date, reason)| {
+                let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map_err(|_| Error::local_file(file_path, format!("`{}` is not a YYYY-MM-DD date", date)))?;
+                Ok(day_off(date, reason.clone()))
+            })
+            .collect()
+    }
+
+    pub fn save(&self, file_path: &str) -> Result<(), Error> {
+        save_json(file_path, self)
+    }
+}
+
+fn day_off(date: NaiveDate, reason: Option<String>) -> DayOff {
+    DayOff {
+        date,
+        kind: DayOffKind::Leave,
+        label: match reason.filter(|reason| !reason.trim().is_empty()) {
+            Some(reason) => format!("Leave: {}", reason.trim()),
+            None => "Leave".to_string(),
+        },
+    }
+}
@@ -1,3 +1,5 @@
+pub mod leave;
+pub mod report;
 pub mod timelog;
 use crate::prelude::*;
 
@@ -5,27 +7,46 @@ extern crate google_sheets4 as sheets4;
 use sheets4::api::{
     Spreadsheet, ValueRange, BatchUpdateSpreadsheetRequest, SheetProperties,
     AddSheetRequest, Request, UpdateCellsRequest, RowData, BatchUpdateSpreadsheetResponse,
-    DimensionProperties, UpdateDimensionPropertiesRequest, DimensionRange, UpdateValuesResponse
+    DimensionProperties, UpdateDimensionPropertiesRequest, DimensionRange, UpdateValuesResponse,
+    AppendCellsRequest, GridRange
 };
 use sheets4::{hyper, hyper_rustls, FieldMask};
 use sheets4::oauth2::{self, authenticator::Authenticator};
 use chrono::{prelude::*, Duration};
-use self::timelog::{GridRangeType, TimeLog};
+use crate::calendar::{DayOff, WorkCalendar};
+use self::timelog::{CellDataType, GridRangeType, TimeLog, TimelogColumn, TimelogMode, TimelogWrite};
 
 pub struct GoogleSheetsApi {
     hub: sheets4::Sheets<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>, 
     spreadsheet_id: String,
+    calendar: WorkCalendar,
+    dry_run: bool,
 }
 
 impl GoogleSheetsApi {
-    pub async fn new(spreadsheet_id: String) -> Self {
-        let client = http_client();
-        let auth = auth(client.clone()).await;
-        let hub = sheets4::Sheets::new(client, auth);
-        Self {
-            hub,
-            spreadsheet_id
+    pub async fn new(config: &Config) -> Result<Self, Error> {
+        let spreadsheet_id = config.require("sheets.sheet_id")?;
+        let api_url = config.get("sheets.api_url");
+        let client = http_client(api_url.is_some());
+        // a pre-issued access token skips the service account flow
+        let mut hub = match config.get("sheets.access_token") {
+            Some(token) => sheets4::Sheets::new(client, token.to_string()),
+            None => {
+                let auth = auth(client.clone(), config.require("sheets.service_account_file")?).await?;
+                sheets4::Sheets::new(client, auth)
+            }
+        };
+        if let Some(url) = api_url {
+            let url = format!("{}/", url.trim_end_matches('/'));
+            hub.base_url(url.clone());
+            hub.root_url(url);
         }
+        Ok(Self {
+            hub,
+            spreadsheet_id,
+            calendar: config.work_calendar()?,
+            dry_run: config.dry_run,
+        })
     }
 
     async fn get_spreadsheet(&self) -> Result<Spreadsheet, Error> {
@@ -34,96 +55,248 @@ impl GoogleSheetsApi {
             .spreadsheets()
             .get(&self.spreadsheet_id)
             .doit()
-            .await?
+            .await
+            .map_err(Error::sheets)?
             .1;
         Ok(result)
     }
 
-    pub async fn post_timelog(&self, task: String, in_office: String, hrs: String) -> Result<(), Error> {
+    pub async fn post_timelog(
+        &self,
+        date: NaiveDate,
+        mut timelog: TimeLog,
+        mode: TimelogMode,
+    ) -> Result<TimelogWrite, Error> {
         println!("{}", "Updating timelog".yellow());
-        let today = Local::now();
-        let day = today.day();
-        let month = today.format("%b").to_string();
-        let year = today.year();
-        let sheet_name = format!("{}({})", month, year);
-        if !self.contains_sheet(&sheet_name).await? {
-            let response = self.create_sheet(&sheet_name).await;
-            match response {
-                Err(_) => {
-                    println!("{}", "Failed to create sheet".red());
-                }
-                Ok(data) => {
-                    println!("{}", "Created sheet".green());
-                    let gid = data.replies.unwrap()[0].clone().add_sheet.unwrap().properties.unwrap().sheet_id;
-                    let timelog = TimeLog::new(
-                        gid,
-                        task,
-                        in_office,
-                        hrs,
-                        sheet_name.clone()
-                    );
-                    let response = self.update_newly_created_sheet(timelog).await;
-                    match response {
-                        Err(_) => {
-                            println!("{}", "Failed to update new sheet".red());
-                        }
-                        Ok(_) => {
-                            println!("{}", "Updated new sheet".green());
-                        }
-                    }
-                }
+        let day = date.day();
+        let sheet_name = sheet_name(date);
+        let columns = timelog.columns.len();
+        let Some(sheet_id) = self.sheet_id(&sheet_name).await? else {
+            if self.dry_run {
+                print_dry_run(
+                    &format!("Would create the sheet and update {}", row_range(&sheet_name, 0, columns, day as usize + 1)),
+                    &serde_json::to_string_pretty(&vec![timelog.row_values(date)]).map_err(Error::sheets)?,
+                );
+                return Ok(TimelogWrite::Written);
             }
-        } else {
-            let timelog = TimeLog::new(
-                None,
-                task,
-                in_office,
-                hrs,
-                sheet_name.clone()
+            let data = self.create_sheet(&sheet_name).await?;
+            println!("{}", "Created sheet".green());
+            timelog.sheet_id = data
+                .replies
+                .and_then(|replies| replies.into_iter().next())
+                .and_then(|reply| reply.add_sheet)
+                .and_then(|add_sheet| add_sheet.properties)
+                .ok_or_else(|| Error::sheets("addSheet reply did not include the new sheet's properties"))?
+                .sheet_id;
+            self.update_newly_created_sheet(timelog, date).await?;
+            println!("{}", "Updated new sheet".green());
+            return Ok(TimelogWrite::Written);
+        };
+        let sheet_range = format!("{}!A:{}", sheet_name, column_letter(columns - 1));
+        let rows = self.read_rows(&sheet_range).await?;
+        let Some(row) = find_row(&sheet_name, &timelog.columns, &rows, date)? else {
+            return self.append_row(sheet_id, &sheet_name, &timelog, date).await;
+        };
+        let range = row_range(&sheet_name, 1, columns, row);
+        let existing = rows[row - 1].get(1..).unwrap_or_default();
+        let Some(values) = timelog.merge(mode, existing, date) else {
+            println!("{}", format!("Timelog row of {} is already filled, leaving it as is", date).yellow());
+            return Ok(TimelogWrite::AlreadyFilled);
+        };
+        if self.dry_run {
+            print_dry_run(
+                &format!("Would update {}", range),
+                &serde_json::to_string_pretty(&vec![values]).map_err(Error::sheets)?,
             );
-            let result = self.update_existing_sheet(timelog, day as i32).await;
-            match result {
-                Err(_) => {
-                    println!("{}", "Failed to update timelog".red());
-                }
-                Ok(_) => {
-                    println!("{}", "Successfully updated Timelog".green());
-                }
-            }
+            return Ok(TimelogWrite::Written);
         }
-        Ok(())
+        self.update_existing_sheet(&range, values).await?;
+        println!("{}", "Successfully updated Timelog".green());
+        Ok(TimelogWrite::Written)
     }
 
-    async fn contains_sheet(&self, sheet_name: &str) -> Result<bool, Error> {
+    // Rows under the header of the month's sheet, which has to exist and match the columns
+    pub async fn read_month(&self, month: NaiveDate, columns: &[TimelogColumn]) -> Result<Vec<Vec<String>>, Error> {
+        let sheet_name = sheet_name(month);
+        if self.sheet_id(&sheet_name).await?.is_none() {
+            return Err(Error::Validation(format!("The timelog has no {} sheet", sheet_name)));
+        }
+        let rows = self
+            .read_rows(&format!("{}!A:{}", sheet_name, column_letter(columns.len() - 1)))
+            .await?;
+        check_header(&sheet_name, columns, &rows)?;
+        Ok(rows.into_iter().skip(1).collect())
+    }
+
+    // Every row of `range` as text, trailing empty cells and rows are left out by the API
+    async fn read_rows(&self, range: &str) -> Result<Vec<Vec<String>>, Error> {
+        let result = self
+            .hub
+            .spreadsheets()
+            .values_get(&self.spreadsheet_id, range)
+            .doit()
+            .await
+            .map_err(Error::sheets)?
+            .1;
+        Ok(result
+            .values
+            .unwrap_or_default()
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|value| match value {
+                        serde_json::Value::String(value) => value,
+                        value => value.to_string(),
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    // Add the row of a date the sheet has no row for under its last row. The cells are written as
+    // text like those of a new sheet, so the date stays MM/DD/YYYY whatever the sheet's locale
+    async fn append_row(&self, sheet_id: i32, sheet_name: &str, timelog: &TimeLog, date: NaiveDate) -> Result<TimelogWrite, Error> {
+        let requests = vec![Request {
+            append_cells: Some(AppendCellsRequest {
+                sheet_id: Some(sheet_id),
+                rows: Some(vec![timelog.get_current_row_data(date)]),
+                fields: Some("user_entered_value".parse().unwrap()),
+            }),
+            ..Default::default()
+        }];
+        if self.dry_run {
+            print_dry_run(
+                &format!("Would add a row for {} to {}", date, sheet_name),
+                &serde_json::to_string_pretty(&requests).map_err(Error::sheets)?,
+            );
+            return Ok(TimelogWrite::Written);
+        }
+        let req = BatchUpdateSpreadsheetRequest {
+            requests: Some(requests),
+            ..Default::default()
+        };
+        self.hub
+            .spreadsheets()
+            .batch_update(req, &self.spreadsheet_id)
+            .doit()
+            .await
+            .map_err(Error::sheets)?;
+        println!("{}", format!("Added a timelog row for {}", date).green());
+        Ok(TimelogWrite::Written)
+    }
+
+    // Id of the sheet called `sheet_name`, None when the spreadsheet doesn't have it
+    async fn sheet_id(&self, sheet_name: &str) -> Result<Option<i32>, Error> {
         let spreadsheet = self.get_spreadsheet().await?;
-        if let Some(sheets) = spreadsheet.sheets {
-            let found_sheet = sheets.iter().find(|sheet| {
-                sheet
-                    .properties
-                    .as_ref()
-                    .map_or(false, |props| props.title == Some(sheet_name.to_string()))
-            });
-            if let Some(_sheet) = found_sheet {
-                return Ok(true);
-            } else {
-                return Ok(false);
+        let sheet_id = spreadsheet
+            .sheets
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|sheet| sheet.properties)
+            .find(|props| props.title.as_deref() == Some(sheet_name))
+            .map(|props| props.sheet_id.unwrap_or_default());
+        Ok(sheet_id)
+    }
+
+    // Colour and label the row of a day off once its month's sheet exists, adding the row if it has none
+    pub async fn mark_day_off(&self, mut timelog: TimeLog, day_off: &DayOff) -> Result<(), Error> {
+        let sheet_name = sheet_name(day_off.date);
+        let Some(sheet_id) = self.sheet_id(&sheet_name).await? else {
+            println!("{}", format!("{} doesn't exist yet, {} is marked when it is created", sheet_name, day_off.date).yellow());
+            return Ok(());
+        };
+        timelog.sheet_id = Some(sheet_id);
+        let columns = timelog.columns.len();
+        let rows = self
+            .read_rows(&format!("{}!A:{}", sheet_name, column_letter(columns - 1)))
+            .await?;
+        let format: FieldMask = "user_entered_format".parse().unwrap();
+        let requests = match find_row(&sheet_name, &timelog.columns, &rows, day_off.date)? {
+            Some(row) => {
+                let row_index = row as i32 - 1;
+                let label_column = timelog.label_column();
+                let cell = |column: usize| GridRange {
+                    sheet_id: Some(sheet_id),
+                    start_row_index: Some(row_index),
+                    end_row_index: Some(row_index + 1),
+                    start_column_index: Some(column as i32),
+                    end_column_index: Some(column as i32 + 1),
+                };
+                let mut requests = vec![Request {
+                    update_cells: Some(UpdateCellsRequest {
+                        range: Some(GridRange {
+                            start_column_index: Some(0),
+                            end_column_index: Some(columns as i32),
+                            ..cell(0)
+                        }),
+                        rows: Some(vec![timelog.get_day_off_row_data(day_off)]),
+                        fields: Some(format),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }];
+                // whatever was logged that day stays, the label only fills an empty cell
+                let label_is_empty = rows[row - 1]
+                    .get(label_column)
+                    .is_none_or(|cell| cell.trim().is_empty());
+                if label_is_empty {
+                    let label = timelog.get_cell_data(CellDataType::ValueOnly, Some(day_off.label.clone()));
+                    requests.push(Request {
+                        update_cells: Some(UpdateCellsRequest {
+                            range: Some(cell(label_column)),
+                            rows: Some(vec![RowData { values: Some(vec![label]) }]),
+                            fields: Some("user_entered_value".parse().unwrap()),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    });
+                }
+                requests
             }
-        } else {
-            return Ok(false);
+            None => vec![Request {
+                append_cells: Some(AppendCellsRequest {
+                    sheet_id: Some(sheet_id),
+                    rows: Some(vec![timelog.get_day_off_row_data(day_off)]),
+                    fields: Some("user_entered_value, user_entered_format".parse().unwrap()),
+                }),
+                ..Default::default()
+            }],
+        };
+        if self.dry_run {
+            print_dry_run(
+                &format!("Would mark {} as {} in {}", day_off.date, day_off.label, sheet_name),
+                &serde_json::to_string_pretty(&requests).map_err(Error::sheets)?,
+            );
+            return Ok(());
         }
+        let req = BatchUpdateSpreadsheetRequest {
+            requests: Some(requests),
+            ..Default::default()
+        };
+        self.hub
+            .spreadsheets()
+            .batch_update(req, &self.spreadsheet_id)
+            .doit()
+            .await
+            .map_err(Error::sheets)?;
+        println!("{}", format!("Marked {} as {} in {}", day_off.date, day_off.label, sheet_name).green());
+        Ok(())
     }
 
     async fn update_newly_created_sheet(
         &self,
         timelog: TimeLog,
+        date: NaiveDate,
     ) -> Result<BatchUpdateSpreadsheetResponse, Error> {
-        let (first_day_of_month, last_day_of_month) = first_and_last_day_of_month();
+        let (first_day_of_month, last_day_of_month) = first_and_last_day_of_month(date);
         let mut dates: Vec<RowData> = Vec::new();
-        let mut current_day = first_day_of_month.clone();
+        let mut current_day = first_day_of_month;
         while current_day <= last_day_of_month {
-            if current_day == Local::now().date_naive() {
+            if current_day == date {
                 dates.push(timelog.get_current_row_data(current_day));
-            } else if current_day.weekday() == Weekday::Sun || current_day.weekday() == Weekday::Sat {
+            } else if let Some(day_off) = self.calendar.day_off(current_day) {
+                dates.push(timelog.get_day_off_row_data(day_off));
+            } else if self.calendar.is_weekend(current_day) {
                 dates.push(timelog.get_weekend_row_data(current_day));
             } else {
                 dates.push(timelog.get_normal_row_data(current_day));
@@ -141,10 +314,30 @@ impl GoogleSheetsApi {
             ..Default::default()
         };
 
-        let dimension_properties = DimensionProperties {
-            pixel_size: Some(520),
-            ..Default::default()
-        };
+        let width_requests = timelog
+            .columns
+            .iter()
+            .enumerate()
+            .filter_map(|(index, column)| Some((index as i32, column.width?)))
+            .map(|(index, width)| Request {
+                update_dimension_properties: Some(
+                    UpdateDimensionPropertiesRequest {
+                        properties: Some(DimensionProperties {
+                            pixel_size: Some(width),
+                            ..Default::default()
+                        }),
+                        fields: Some(dimension_fields.clone()),
+                        range: Some(DimensionRange {
+                            sheet_id: timelog.sheet_id,
+                            dimension: Some("COLUMNS".to_string()),
+                            start_index: Some(index),
+                            end_index: Some(index + 1),
+                        }),
+                        ..Default::default()
+                    }
+                ),
+                ..Default::default()
+            });
 
         let req = BatchUpdateSpreadsheetRequest {
             requests: Some(vec![
@@ -166,23 +359,10 @@ impl GoogleSheetsApi {
                     }),
                     ..Default::default()
                 },
-                Request {
-                    update_dimension_properties: Some(
-                        UpdateDimensionPropertiesRequest {
-                            properties: Some(dimension_properties),
-                            fields: Some(dimension_fields),
-                            range: Some(DimensionRange {
-                                sheet_id: timelog.sheet_id,
-                                dimension: Some("COLUMNS".to_string()),
-                                start_index: Some(2),
-                                end_index: Some(3),
-                            }),
-                            ..Default::default()
-                        }
-                    ),
-                    ..Default::default()
-                }
-            ]),
+            ]
+            .into_iter()
+            .chain(width_requests)
+            .collect()),
             ..Default::default()
         };
 
@@ -191,33 +371,31 @@ impl GoogleSheetsApi {
             .spreadsheets()
             .batch_update(req, &self.spreadsheet_id)
             .doit()
-            .await?;
+            .await
+            .map_err(Error::sheets)?;
         Ok(result.1)
     }
 
     async fn update_existing_sheet(
         &self,
-        timelog: TimeLog,
-        day: i32,
+        range: &str,
+        values: Vec<String>,
     ) -> Result<UpdateValuesResponse, Error> {
-        let range = format!("{}!B{}:D{}", timelog.sheet_name, day + 1, day + 1);
-        let task = serde_json::Value::String(timelog.task.to_string());
-        let in_office = serde_json::Value::String(timelog.in_office.to_string());
-        let hrs = serde_json::Value::String(timelog.hrs.to_string());
-        let data = vec![vec![in_office, task, hrs]];
+        let data = vec![values.into_iter().map(serde_json::Value::String).collect()];
         let req = ValueRange {
             major_dimension: Some("ROWS".to_string()),
-            values: Some(data.clone()),
-            range: Some(range.clone()),
+            values: Some(data),
+            range: Some(range.to_string()),
         };
 
         let result = self
             .hub
             .spreadsheets()
-            .values_update(req, &self.spreadsheet_id, &range)
+            .values_update(req, &self.spreadsheet_id, range)
             .value_input_option("USER_ENTERED")
             .doit()
-            .await?;
+            .await
+            .map_err(Error::sheets)?;
         Ok(result.1)
     }
 
@@ -249,43 +427,105 @@ impl GoogleSheetsApi {
             .spreadsheets()
             .batch_update(req, &self.spreadsheet_id)
             .doit()
-            .await?
+            .await
+            .map_err(Error::sheets)?
             .1;
         Ok(result)
     }
 }
 
 
-fn http_client() -> hyper::Client<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>> {
-    return hyper::Client::builder().build(
-        hyper_rustls::HttpsConnectorBuilder::new()
-            .with_native_roots()
-            .https_only()
+// Each month has its own sheet, e.g. Oct(2026)
+fn sheet_name(date: NaiveDate) -> String {
+    format!("{}({})", date.format("%b"), date.year())
+}
+
+// Cells from the `first` column to the last of `columns` in `row`, row 1 being the header
+fn row_range(sheet_name: &str, first: usize, columns: usize, row: usize) -> String {
+    format!("{}!{}{}:{}{}", sheet_name, column_letter(first), row, column_letter(columns - 1), row)
+}
+
+// Row number of `date` going by the %m/%d/%Y dates of column A, once the header is known to match
+fn find_row(
+    sheet_name: &str,
+    columns: &[TimelogColumn],
+    rows: &[Vec<String>],
+    date: NaiveDate,
+) -> Result<Option<usize>, Error> {
+    check_header(sheet_name, columns, rows)?;
+    let row = rows
+        .iter()
+        .enumerate()
+        .skip(1)
+        .find(|(_, row)| {
+            row.first()
+                .and_then(|cell| NaiveDate::parse_from_str(cell.trim(), "%m/%d/%Y").ok())
+                == Some(date)
+        })
+        .map(|(index, _)| index + 1);
+    Ok(row)
+}
+
+// The first row has to hold the column names in order, else the rows can't be read by column
+fn check_header(sheet_name: &str, columns: &[TimelogColumn], rows: &[Vec<String>]) -> Result<(), Error> {
+    let header: Vec<&str> = rows
+        .first()
+        .map(|row| row.iter().map(|cell| cell.trim()).collect())
+        .unwrap_or_default();
+    let expected: Vec<&str> = columns.iter().map(|column| column.name.as_str()).collect();
+    if header != expected {
+        return Err(Error::Validation(format!(
+            "Sheet {} doesn't match the timelog columns, expected the header `{}` but found `{}`. Fix the sheet or sheets.columns",
+            sheet_name,
+            expected.join(" | "),
+            header.join(" | ")
+        )));
+    }
+    Ok(())
+}
+
+// A1 notation of the 0 based column `index`: A, B, .., Z, AA, AB, ..
+fn column_letter(index: usize) -> String {
+    let mut letters = String::new();
+    let mut index = index + 1;
+    while index > 0 {
+        index -= 1;
+        letters.insert(0, (b'A' + (index % 26) as u8) as char);
+        index /= 26;
+    }
+    letters
+}
+
+// Plain http is only allowed for an overridden sheets.api_url, e.g. a local stand-in server
+fn http_client(allow_http: bool) -> hyper::Client<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>> {
+    let builder = hyper_rustls::HttpsConnectorBuilder::new().with_native_roots();
+    let builder = if allow_http { builder.https_or_http() } else { builder.https_only() };
+    hyper::Client::builder().build(
+        builder
             .enable_http1()
             .enable_http2()
             .build(),
-    );
+    )
 }
 
 async fn auth(
     client: hyper::Client<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>,
-) -> Authenticator<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>> {
-    let service_account = env::var("SERVICE_ACCOUNT_FILE").unwrap();
-    let secret: oauth2::ServiceAccountKey = oauth2::read_service_account_key(service_account)
+    service_account: String,
+) -> Result<Authenticator<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>, Error> {
+    let secret: oauth2::ServiceAccountKey = oauth2::read_service_account_key(&service_account)
         .await
-        .expect("secret not found");
+        .map_err(|e| Error::local_file(&service_account, format!("{}, expected a service account key json", e)))?;
 
-    return oauth2::ServiceAccountAuthenticator::with_client(secret, client.clone())
+    oauth2::ServiceAccountAuthenticator::with_client(secret, client.clone())
         .build()
         .await
-        .expect("could not create an authenticator");
+        .map_err(|e| Error::sheets(format!("could not create an authenticator: {}", e)))
 }
 
-fn first_and_last_day_of_month() ->(NaiveDate, NaiveDate) {
-    let today = Local::now();
-    let first = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap();
-    let last = NaiveDate::from_ymd_opt(today.year(), today.month() + 1, 1)
-        .unwrap_or(NaiveDate::from_ymd_opt(today.year() + 1, 1, 1).unwrap())
+fn first_and_last_day_of_month(date: NaiveDate) ->(NaiveDate, NaiveDate) {
+    let first = NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap();
+    let last = NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+        .unwrap_or(NaiveDate::from_ymd_opt(date.year() + 1, 1, 1).unwrap())
         .pred_opt()
         .unwrap();
         
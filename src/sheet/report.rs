@@ -0,0 +1,270 @@
+use crate::prelude::*;
+use crate::calendar::WorkCalendar;
+use super::timelog::{ColumnSource, TimelogColumn};
+use chrono::prelude::*;
+use clap::ValueEnum;
+use handlebars::Handlebars;
+use std::path::Path;
+
+// Printable on its own, the browser's "Save as PDF" gives the invoice
+const INVOICE_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Timesheet {{month}}</title>
+<style>
+  @page { size: A4; margin: 20mm; }
+  body { font-family: Helvetica, Arial, sans-serif; font-size: 11pt; color: #222; }
+  h1 { font-size: 18pt; margin-bottom: 4pt; }
+  table { width: 100%; border-collapse: collapse; margin-top: 16pt; }
+  th, td { border-bottom: 1px solid #ccc; padding: 4pt 6pt; text-align: left; vertical-align: top; }
+  td.task { white-space: pre-line; }
+  .number { text-align: right; white-space: nowrap; }
+  tfoot td { font-weight: bold; border-bottom: none; }
+  tr { page-break-inside: avoid; }
+</style>
+</head>
+<body>
+<h1>Timesheet {{month}}</h1>
+<p>{{office_days}} office day(s), {{wfh_days}} WFH day(s)</p>
+<table>
+  <thead>
+    <tr><th>Date</th><th>In Office</th><th>Task</th><th class="number">Hours</th>{{#if rate}}<th class="number">Amount</th>{{/if}}</tr>
+  </thead>
+  <tbody>
+{{#each lines}}    <tr><td>{{date}}</td><td>{{in_office}}</td><td class="task">{{task}}</td><td class="number">{{hours}}</td>{{#if ../rate}}<td class="number">{{amount}}</td>{{/if}}</tr>
+{{/each}}  </tbody>
+  <tfoot>
+    <tr><td colspan="3">Total</td><td class="number">{{total_hours}}</td>{{#if rate}}<td class="number">{{total_amount}}</td>{{/if}}</tr>
+  </tfoot>
+</table>
+{{#if rate}}<p>Rate: {{rate}} per hour</p>{{/if}}
+</body>
+</html>
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    Csv,
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    // Going by the extension of --output
+    pub fn from_path(path: &str) -> Option<Self> {
+        match Path::new(path).extension()?.to_str()?.to_lowercase().as_str() {
+            "csv" => Some(ReportFormat::Csv),
+            "md" | "markdown" => Some(ReportFormat::Markdown),
+            "html" | "htm" => Some(ReportFormat::Html),
+            _ => None,
+        }
+    }
+}
+
+// A day with something logged on it
+#[derive(Debug)]
+pub struct ReportDay {
+    pub date: NaiveDate,
+    pub in_office: String,
+    pub task: String,
+    pub hours: f64,
+}
+
+#[derive(Debug)]
+pub struct MonthReport {
+    pub month: String,
+    pub days: Vec<ReportDay>,
+    pub total_hours: f64,
+    pub office_days: usize,
+    pub wfh_days: usize,
+    pub empty_days: Vec<NaiveDate>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct InvoiceLine {
+    date: String,
+    in_office: String,
+    task: String,
+    hours: String,
+    amount: String,
+}
+
+#[derive(Serialize)]
+struct Invoice<'a> {
+    month: &'a str,
+    office_days: usize,
+    wfh_days: usize,
+    lines: Vec<InvoiceLine>,
+    total_hours: String,
+    rate: Option<String>,
+    total_amount: String,
+}
+
+impl MonthReport {
+    // `rows` are the sheet's rows under its header, working days up to `until` are expected to be filled.
+    // Rows of holidays and leave only count when hours were logged on them.
+    pub fn new(
+        month: NaiveDate,
+        columns: &[TimelogColumn],
+        rows: &[Vec<String>],
+        calendar: &WorkCalendar,
+        until: NaiveDate,
+    ) -> Self {
+        let cell = |row: &[String], source: ColumnSource| {
+            columns
+                .iter()
+                .position(|column| column.source == source)
+                .and_then(|index| row.get(index))
+                .map(|cell| cell.trim().to_string())
+                .unwrap_or_default()
+        };
+        let mut days = Vec::new();
+        let mut warnings = Vec::new();
+        for row in rows {
+            let Some(date) = row
+                .first()
+                .and_then(|cell| NaiveDate::parse_from_str(cell.trim(), "%m/%d/%Y").ok())
+                .filter(|date| date.year() == month.year() && date.month() == month.month())
+            else {
+                continue;
+            };
+            let hours = cell(row, ColumnSource::Hours);
+            let task = cell(row, ColumnSource::Task);
+            let filled = row.iter().skip(1).any(|cell| !cell.trim().is_empty());
+            if !filled || (calendar.day_off(date).is_some() && hours.is_empty()) {
+                continue;
+            }
+            let hours = match hours.parse::<f64>() {
+                Ok(hours) => hours,
+                Err(_) if hours.is_empty() => 0.0,
+                Err(_) => {
+                    warnings.push(format!("hrs of {} is not a number: `{}`", date, hours));
+                    0.0
+                }
+            };
+            days.push(ReportDay {
+                date,
+                in_office: cell(row, ColumnSource::InOffice),
+                task,
+                hours,
+            });
+        }
+        days.sort_by_key(|day| day.date);
+
+        let empty_days = month
+            .iter_days()
+            .take_while(|date| date.month() == month.month() && *date <= until)
+            .filter(|date| calendar.is_working_day(*date) && !days.iter().any(|day| day.date == *date))
+            .collect();
+        let wfh_days = days.iter().filter(|day| day.in_office.eq_ignore_ascii_case("wfh")).count();
+        let office_days = days.iter().filter(|day| !day.in_office.is_empty()).count() - wfh_days;
+        Self {
+            month: month.format("%B %Y").to_string(),
+            total_hours: days.iter().map(|day| day.hours).sum(),
+            days,
+            office_days,
+            wfh_days,
+            empty_days,
+            warnings,
+        }
+    }
+
+    pub fn export(&self, format: ReportFormat, rate: Option<f64>) -> Result<String, Error> {
+        match format {
+            ReportFormat::Csv => self.csv(rate),
+            ReportFormat::Markdown => Ok(self.markdown(rate)),
+            ReportFormat::Html => self.html(rate),
+        }
+    }
+
+    fn csv(&self, rate: Option<f64>) -> Result<String, Error> {
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        let mut header = vec!["date", "in_office", "task", "hours"];
+        if rate.is_some() {
+            header.push("amount");
+        }
+        wtr.write_record(&header).map_err(csv_error)?;
+        for day in &self.days {
+            let mut record = vec![day.date.to_string(), day.in_office.clone(), day.task.clone(), decimal(day.hours)];
+            if let Some(rate) = rate {
+                record.push(decimal(day.hours * rate));
+            }
+            wtr.write_record(&record).map_err(csv_error)?;
+        }
+        let mut total = vec!["Total".to_string(), String::new(), String::new(), decimal(self.total_hours)];
+        if let Some(rate) = rate {
+            total.push(decimal(self.total_hours * rate));
+        }
+        wtr.write_record(&total).map_err(csv_error)?;
+        let data = wtr.into_inner().map_err(csv_error)?;
+        String::from_utf8(data).map_err(csv_error)
+    }
+
+    fn markdown(&self, rate: Option<f64>) -> String {
+        let cell = |value: &str| value.trim().replace('|', "\\|").replace('\n', "<br>");
+        let mut markdown = format!("# Timesheet {}\n\n", self.month);
+        markdown.push_str(&format!("{} office day(s), {} WFH day(s)\n\n", self.office_days, self.wfh_days));
+        match rate {
+            Some(_) => markdown.push_str("| Date | In Office | Task | Hours | Amount |\n|---|---|---|--:|--:|\n"),
+            None => markdown.push_str("| Date | In Office | Task | Hours |\n|---|---|---|--:|\n"),
+        }
+        for day in &self.days {
+            markdown.push_str(&format!("| {} | {} | {} | {} |", day.date, cell(&day.in_office), cell(&day.task), decimal(day.hours)));
+            if let Some(rate) = rate {
+                markdown.push_str(&format!(" {} |", decimal(day.hours * rate)));
+            }
+            markdown.push('\n');
+        }
+        markdown.push_str(&format!("| **Total** | | | **{}** |", decimal(self.total_hours)));
+        if let Some(rate) = rate {
+            markdown.push_str(&format!(" **{}** |", decimal(self.total_hours * rate)));
+        }
+        markdown.push('\n');
+        if !self.empty_days.is_empty() {
+            markdown.push_str(&format!(
+                "\nEmpty working days: {}\n",
+                self.empty_days.iter().map(|date| date.to_string()).collect::<Vec<String>>().join(", ")
+            ));
+        }
+        markdown
+    }
+
+    fn html(&self, rate: Option<f64>) -> Result<String, Error> {
+        let invoice = Invoice {
+            month: &self.month,
+            office_days: self.office_days,
+            wfh_days: self.wfh_days,
+            lines: self
+                .days
+                .iter()
+                .map(|day| InvoiceLine {
+                    date: day.date.format("%a %b %-d").to_string(),
+                    in_office: day.in_office.clone(),
+                    task: day.task.clone(),
+                    hours: decimal(day.hours),
+                    amount: rate.map(|rate| decimal(day.hours * rate)).unwrap_or_default(),
+                })
+                .collect(),
+            total_hours: decimal(self.total_hours),
+            rate: rate.map(decimal),
+            total_amount: rate.map(|rate| decimal(self.total_hours * rate)).unwrap_or_default(),
+        };
+        let mut handlebars = Handlebars::new();
+        handlebars.set_strict_mode(true);
+        handlebars
+            .render_template(INVOICE_TEMPLATE, &invoice)
+            .map_err(|e| Error::Validation(format!("Invoice template failed to render: {}", e)))
+    }
+}
+
+// Hours and amounts alike, sums of halves and quarters pick up float noise, e.g. 159.50000000000003
+pub fn decimal(value: f64) -> String {
+    format!("{:.2}", value)
+}
+
+// The CSV is written to memory, so a failure is the export's and not the --output file's
+fn csv_error(error: impl std::fmt::Display) -> Error {
+    Error::Validation(format!("CSV export failed: {}", error))
+}
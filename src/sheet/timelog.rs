@@ -1,30 +1,175 @@
 use chrono::NaiveDate;
+use clap::ValueEnum;
 use google_sheets4::api::{GridRange, RowData, CellData, ExtendedValue, CellFormat, Color};
+use serde::{Deserialize, Serialize};
+use crate::calendar::{DayOff, DayOffKind};
+
+// What to do when today's row already has something in it
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum TimelogMode {
+    /// replace every column that isn't filled by hand
+    Overwrite,
+    /// add the tasks missing from the task column, keep the rest
+    Append,
+    /// leave the row alone
+    SkipIfFilled,
+}
+
+// What became of the row `post_timelog` was asked to write
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimelogWrite {
+    Written,
+    AlreadyFilled,
+}
+
+// Where the value of a timelog column comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnSource {
+    Date,
+    InOffice,
+    Task,
+    Hours,
+    Projects,
+    Text,
+    #[default]
+    Manual,
+}
+
+// One `[[sheets.columns]]` entry, columns are laid out in the order they are listed
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TimelogColumn {
+    pub name: String,
+    #[serde(default)]
+    pub source: ColumnSource,
+    pub width: Option<i32>,
+    pub value: Option<String>,
+}
+
+impl TimelogColumn {
+    fn new(name: &str, source: ColumnSource, width: Option<i32>) -> Self {
+        Self {
+            name: name.to_string(),
+            source,
+            width,
+            value: None,
+        }
+    }
+}
+
+// The layout used when sheets.columns isn't set
+pub fn default_columns() -> Vec<TimelogColumn> {
+    vec![
+        TimelogColumn::new("Date", ColumnSource::Date, None),
+        TimelogColumn::new("In Office", ColumnSource::InOffice, None),
+        TimelogColumn::new("Task", ColumnSource::Task, Some(520)),
+        TimelogColumn::new("hrs", ColumnSource::Hours, None),
+    ]
+}
+
+// Rows are found by their date, so it has to be the first column and the only one
+pub fn check_columns(columns: &[TimelogColumn]) -> Result<(), String> {
+    if columns.first().map(|column| column.source) != Some(ColumnSource::Date) {
+        return Err("the first column must have source = \"date\"".to_string());
+    }
+    if columns.len() < 2 {
+        return Err("there has to be a column after the date".to_string());
+    }
+    for (index, column) in columns.iter().enumerate() {
+        if column.name.trim().is_empty() {
+            return Err("every column needs a name".to_string());
+        }
+        if column.source == ColumnSource::Date && index > 0 {
+            return Err(format!("`{}` can't be a second date column", column.name));
+        }
+        if column.source == ColumnSource::Text && column.value.is_none() {
+            return Err(format!("`{}` has source = \"text\" but no value", column.name));
+        }
+        if column.width.is_some_and(|width| width <= 0) {
+            return Err(format!("width of `{}` must be positive", column.name));
+        }
+    }
+    Ok(())
+}
 
 pub struct TimeLog {
     pub sheet_id: Option<i32>,
+    pub columns: Vec<TimelogColumn>,
     pub task: String,
     pub in_office: String,
     pub hrs: String,
-    pub sheet_name: String
+    pub projects: Vec<String>,
 }
 
 impl TimeLog {
     pub fn new(
-        sheet_id: Option<i32>,
+        columns: Vec<TimelogColumn>,
         task: String,
         in_office: String,
         hrs: String,
-        sheet_name: String
+        projects: Vec<String>,
     ) -> Self {
         Self {
-            sheet_id,
+            sheet_id: None,
+            columns,
             task,
             in_office,
             hrs,
-            sheet_name
+            projects,
+        }
+    }
+
+    // Only the layout, for rows that aren't about today's tasks
+    pub fn layout(columns: Vec<TimelogColumn>) -> Self {
+        Self::new(columns, String::new(), String::new(), String::new(), Vec::new())
+    }
+
+    // What goes in `column` on the row of `date`, None for columns filled by hand
+    fn value_of(&self, column: &TimelogColumn, date: NaiveDate) -> Option<String> {
+        match column.source {
+            ColumnSource::Date => Some(date.format("%m/%d/%Y").to_string()),
+            ColumnSource::InOffice => Some(self.in_office.clone()),
+            ColumnSource::Task => Some(self.task.clone()),
+            ColumnSource::Hours => Some(self.hrs.clone()),
+            ColumnSource::Projects => Some(self.projects.join(", ")),
+            ColumnSource::Text => column.value.clone(),
+            ColumnSource::Manual => None,
         }
     }
+
+    // Every cell of the row of `date`, the date included
+    pub fn row_values(&self, date: NaiveDate) -> Vec<String> {
+        self.columns
+            .iter()
+            .map(|column| self.value_of(column, date).unwrap_or_default())
+            .collect()
+    }
+
+    // Cells after the date to write over `existing`, None when the row should stay as it is.
+    // Columns filled by hand keep what is already there whatever the mode.
+    pub fn merge(&self, mode: TimelogMode, existing: &[String], date: NaiveDate) -> Option<Vec<String>> {
+        let cell = |index: usize| existing.get(index).map(String::as_str).unwrap_or_default();
+        let filled = existing.iter().any(|cell| !cell.trim().is_empty());
+        if mode == TimelogMode::SkipIfFilled && filled {
+            return None;
+        }
+        let merged: Vec<String> = self
+            .columns
+            .iter()
+            .skip(1)
+            .enumerate()
+            .map(|(index, column)| match self.value_of(column, date) {
+                None => cell(index).to_string(),
+                Some(value) if mode != TimelogMode::Append => value,
+                Some(value) if column.source == ColumnSource::Task => append_lines(cell(index).trim(), &value),
+                Some(value) if cell(index).trim().is_empty() => value,
+                Some(_) => cell(index).trim().to_string(),
+            })
+            .collect();
+        let unchanged = merged.iter().enumerate().all(|(index, value)| cell(index).trim() == value);
+        (mode != TimelogMode::Append || !unchanged).then_some(merged)
+    }
+
     pub fn get_grid_range(
         &self,
         grid_range_type: GridRangeType,
@@ -37,7 +182,7 @@ impl TimeLog {
                     start_row_index: Some(0),
                     end_row_index: Some(end_row_index),
                     start_column_index: Some(0),
-                    end_column_index: Some(4),
+                    end_column_index: Some(self.columns.len() as i32),
                 }
             }
             GridRangeType::Date => {
@@ -46,8 +191,7 @@ impl TimeLog {
                     start_row_index: Some(1),
                     end_row_index: Some(end_row_index),
                     start_column_index: Some(0),
-                    end_column_index: Some(26),
-
+                    end_column_index: Some(self.columns.len() as i32),
                 }
             }
         }
@@ -55,41 +199,57 @@ impl TimeLog {
 
     pub fn get_current_row_data(&self, current_day: NaiveDate) -> RowData {
         RowData {
-            values: Some(vec![
-                self.get_cell_data(
-                    CellDataType::ValueOnly,
-                    Some(current_day.format("%m/%d/%Y").to_string())
-                ),
-                self.get_cell_data(
-                    CellDataType::ValueOnly,
-                    Some(self.in_office.to_string())
-                ),
-                self.get_cell_data(
-                    CellDataType::ValueOnly,
-                    Some(self.task.to_string())
-                ),
-                self.get_cell_data(
-                    CellDataType::ValueOnly,
-                    Some(self.hrs.to_string())
-                ),
-            ])
+            values: Some(
+                self.columns
+                    .iter()
+                    .map(|column| match self.value_of(column, current_day) {
+                        Some(value) => self.get_cell_data(CellDataType::ValueOnly, Some(value)),
+                        None => CellData::default(),
+                    })
+                    .collect()
+            )
         }
     }
 
     pub fn get_weekend_row_data(&self, current_day: NaiveDate) -> RowData {
-        let colored_cells = vec![self.get_cell_data(CellDataType::StyleOnly, None); 25];
-        let mut values = Vec::new();
-        values.push(
-            self.get_cell_data(
-                CellDataType::StyleAndValue,
-                Some(current_day.format("%m/%d/%Y").to_string()),
-            )
-        );
-        values.extend(colored_cells);
+        self.get_colored_row_data(current_day, RowColor::Weekend, None)
+    }
+
+    pub fn get_day_off_row_data(&self, day_off: &DayOff) -> RowData {
+        self.get_colored_row_data(day_off.date, day_off.kind.into(), Some(&day_off.label))
+    }
+
+    // Date and `label` over `color`, every other cell of the row coloured too
+    fn get_colored_row_data(&self, current_day: NaiveDate, color: RowColor, label: Option<&str>) -> RowData {
+        let label_column = self.label_column();
+        let values = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let value = match column.source {
+                    ColumnSource::Date => Some(current_day.format("%m/%d/%Y").to_string()),
+                    _ if index == label_column => label.map(str::to_string),
+                    _ => None,
+                };
+                match value {
+                    Some(value) => self.get_cell_data(CellDataType::StyleAndValue(color), Some(value)),
+                    None => self.get_cell_data(CellDataType::StyleOnly(color), None),
+                }
+            })
+            .collect();
 
         RowData { values: Some(values) }
     }
 
+    // Holidays and leave are labelled in the task column, or the one after the date without it
+    pub fn label_column(&self) -> usize {
+        self.columns
+            .iter()
+            .position(|column| column.source == ColumnSource::Task)
+            .unwrap_or(1)
+    }
+
     pub fn get_normal_row_data(&self, current_day: NaiveDate) -> RowData {
         RowData {
             values: Some(vec![
@@ -103,10 +263,10 @@ impl TimeLog {
 
     pub fn get_header_row_data(&self) -> RowData {
         let mut cells_data_for_headers = Vec::new();
-        for value in vec!["Date", "In Office", "Task", "hrs"] {
+        for column in self.columns.iter() {
             cells_data_for_headers.push(
                 self.get_cell_data(CellDataType::ValueOnly, Some(
-                    value.to_string(),
+                    column.name.to_string(),
                 ))
             )            
         }
@@ -121,36 +281,22 @@ impl TimeLog {
         value: Option<String>,
     ) -> CellData {
         match cell_data_type {
-            CellDataType::StyleOnly => {
+            CellDataType::StyleOnly(color) => {
                CellData {
                    user_entered_format: Some(
                        CellFormat {
-                           background_color: Some(
-                               Color {
-                                   red: Some(1.0),
-                                   green: Some(0.65),
-                                   blue: Some(0.0),
-                                   ..Default::default()
-                               }
-                           ),
+                           background_color: Some(color.color()),
                            ..Default::default()
                        }
                    ),
                    ..Default::default()
                }
             }
-            CellDataType::StyleAndValue => {
+            CellDataType::StyleAndValue(color) => {
                 CellData {
                     user_entered_format: Some(
                         CellFormat {
-                            background_color: Some(
-                                Color {
-                                    red: Some(1.0),
-                                    green: Some(0.65),
-                                    blue: Some(0.0),
-                                    ..Default::default()
-                                }
-                            ),
+                            background_color: Some(color.color()),
                             ..Default::default()
                         }
                     ),
@@ -185,9 +331,93 @@ pub enum GridRangeType {
 }
 
 pub enum CellDataType {
-    StyleOnly,
-    StyleAndValue,
+    StyleOnly(RowColor),
+    StyleAndValue(RowColor),
     ValueOnly
 }
 
+// Background of the rows nobody works on
+#[derive(Debug, Clone, Copy)]
+pub enum RowColor {
+    Weekend,
+    Holiday,
+    Leave,
+}
+
+impl RowColor {
+    fn color(self) -> Color {
+        let (red, green, blue) = match self {
+            RowColor::Weekend => (1.0, 0.65, 0.0),
+            RowColor::Holiday => (0.6, 0.8, 1.0),
+            RowColor::Leave => (0.7, 0.9, 0.6),
+        };
+        Color {
+            red: Some(red),
+            green: Some(green),
+            blue: Some(blue),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<DayOffKind> for RowColor {
+    fn from(kind: DayOffKind) -> Self {
+        match kind {
+            DayOffKind::Holiday => RowColor::Holiday,
+            DayOffKind::Leave => RowColor::Leave,
+        }
+    }
+}
 
+// `lines` added to `task`, skipping the tasks already in it. Tasks under a project heading go
+// at the end of that project's tasks in the cell and are only compared with those, unless the
+// cell has no such heading, e.g. when the day was logged with --no-group before
+fn append_lines(task: &str, lines: &str) -> String {
+    let mut cell: Vec<String> = task.lines().map(str::to_string).collect();
+    for (heading, tasks) in sections(lines) {
+        let start = heading.and_then(|heading| cell.iter().position(|line| line.trim() == heading.trim()));
+        let block = match start {
+            Some(start) => start + 1..(start + 1..cell.len()).find(|&index| !is_task(&cell[index])).unwrap_or(cell.len()),
+            None => 0..cell.len(),
+        };
+        let new: Vec<String> = tasks
+            .into_iter()
+            .filter(|line| !cell[block.clone()].iter().any(|existing| task_name(existing) == task_name(line)))
+            .map(str::to_string)
+            .collect();
+        if new.is_empty() {
+            continue;
+        }
+        let end = match (heading, start) {
+            (Some(heading), None) => {
+                cell.push(heading.to_string());
+                cell.len()
+            }
+            _ => block.end,
+        };
+        cell.splice(end..end, new);
+    }
+    cell.join("\n")
+}
+
+// Task text split at its project headings, tasks before the first heading have none
+fn sections(lines: &str) -> Vec<(Option<&str>, Vec<&str>)> {
+    let mut sections: Vec<(Option<&str>, Vec<&str>)> = Vec::new();
+    for line in lines.lines().filter(|line| !line.trim().is_empty()) {
+        match sections.last_mut() {
+            _ if !is_task(line) => sections.push((Some(line), Vec::new())),
+            Some((_, tasks)) => tasks.push(line),
+            None => sections.push((None, vec![line])),
+        }
+    }
+    sections
+}
+
+fn is_task(line: &str) -> bool {
+    line.trim_start().starts_with('•')
+}
+
+// A line without its bullet, so " • Fix login" and "•Fix login" are the same task
+fn task_name(line: &str) -> &str {
+    line.trim().trim_start_matches('•').trim()
+}
@@ -1,49 +1,233 @@
+pub mod post_log;
+pub mod slack_response;
 pub mod slack_message;
 
 use crate::prelude::*;
+use crate::calendar;
+use crate::notifier::{Notifier, StandUp};
+use chrono::prelude::*;
+use post_log::{Post, PostLog, Scheduled};
 use reqwest::{header::HeaderMap, Client};
+use slack_message::StandupMessage;
+
+const DEFAULT_API_URL: &str = "https://slack.com/api";
 
 pub struct SlackApi {
     client: Client,
     headers: HeaderMap,
     base_url: String,
+    dry_run: bool,
+    channel: Option<String>,
+    thread_pattern: Option<String>,
+    posts_file: Option<String>,
+    options: PostOptions,
+}
+
+// How a stand-up is posted: in a given thread, as an edit of the last post or later on
+#[derive(Debug, Default, Clone)]
+pub struct PostOptions {
+    pub thread_ts: Option<String>,
+    pub update: bool,
+    pub post_at: Option<DateTime<Local>>,
 }
 
 impl SlackApi {
-    pub fn new() -> Self {
-        let api_key = env::var("SLACK_USER_TOKEN").unwrap();
+    pub fn new(config: &Config) -> Result<Self, Error> {
+        let api_key = config.require("slack.user_token")?;
         let mut headers = HeaderMap::new();
         headers.insert(
             "Authorization",
-            format!("Bearer {}", api_key).parse().unwrap(),
+            format!("Bearer {}", api_key)
+                .parse()
+                .map_err(|_| Error::config("slack.user_token", "contains characters not allowed in an HTTP header"))?,
         );
         headers.insert("Accept", "application/json".parse().unwrap());
-        Self {
-            client: reqwest::Client::new(),            
+        Ok(Self {
+            client: reqwest::Client::new(),
             headers,
-            base_url: "https://slack.com/api".to_string(),
-        }
+            base_url: config
+                .get("slack.api_url")
+                .unwrap_or(DEFAULT_API_URL)
+                .trim_end_matches('/')
+                .to_string(),
+            dry_run: config.dry_run,
+            channel: config.get("slack.channel").map(str::to_string),
+            thread_pattern: config.get("slack.thread_pattern").map(str::to_string),
+            posts_file: config.slack_posts().ok(),
+            options: PostOptions::default(),
+        })
     }
 
-    pub async fn send_message(&self, message: String, channel: String) -> Result<(), Error> {
+    pub fn with_options(mut self, options: PostOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub async fn send_message(
+        &self,
+        message: slack_message::StandupMessage,
+    ) -> Result<slack_message::MessageResponse, Error> {
         println!("{}", "Sending message to Slack".yellow());
+        self.post_message("chat.postMessage", &message).await
+    }
+
+    // Replace a posted stand-up, `message.ts` says which one
+    pub async fn update_message(
+        &self,
+        message: slack_message::StandupMessage,
+    ) -> Result<slack_message::MessageResponse, Error> {
+        println!("{}", "Updating message on Slack".yellow());
+        self.post_message("chat.update", &message).await
+    }
+
+    pub async fn schedule_message(
+        &self,
+        message: slack_message::StandupMessage,
+    ) -> Result<slack_message::ScheduleResponse, Error> {
+        println!("{}", "Scheduling message on Slack".yellow());
+        if self.dry_run {
+            print_dry_run(
+                &format!("Would POST {}/chat.scheduleMessage", self.base_url),
+                &serde_json::to_string_pretty(&message).map_err(Error::slack)?,
+            );
+            return Ok(slack_message::ScheduleResponse::default());
+        }
+        let request = self.client
+            .post(format!("{}/chat.scheduleMessage", self.base_url))
+            .json(&message)
+            .headers(self.headers.clone());
+        self.api_call("chat.scheduleMessage", request).await
+    }
 
-        let response = self.client
-        .post(&format!("{}/chat.postMessage", self.base_url))
-        .json(&slack_message::StandupMessage::new(channel, message))
-        .headers(self.headers.clone())
-        .send()
-        .await?
-        .json::<slack_message::MessageResponse>()
-        .await?;
-
-        if response.ok {
-            println!("{}", "Message sent to Slack".green());
-        } else {
-            println!("{}", "Failed to send message to Slack".red());
-            println!("{}", response.error.unwrap().red());
+    pub async fn delete_scheduled_message(&self, channel: String, scheduled_message_id: String) -> Result<(), Error> {
+        let body = slack_message::DeleteScheduled { channel, scheduled_message_id };
+        if self.dry_run {
+            print_dry_run(
+                &format!("Would POST {}/chat.deleteScheduledMessage", self.base_url),
+                &serde_json::to_string_pretty(&body).map_err(Error::slack)?,
+            );
+            return Ok(());
         }
+        let request = self.client
+            .post(format!("{}/chat.deleteScheduledMessage", self.base_url))
+            .json(&body)
+            .headers(self.headers.clone());
+        self.api_call::<serde_json::Value>("chat.deleteScheduledMessage", request).await?;
+        Ok(())
+    }
 
+    // ts of the newest message since `oldest` (unix seconds) whose text contains `pattern`
+    pub async fn find_thread(&self, channel: &str, pattern: &str, oldest: i64) -> Result<Option<String>, Error> {
+        println!("{}", "Looking for today's thread on Slack".yellow());
+        let request = self.client
+            .get(format!("{}/conversations.history", self.base_url))
+            .query(&[("channel", channel), ("oldest", &oldest.to_string()), ("limit", "200")])
+            .headers(self.headers.clone());
+        let response: slack_message::HistoryResponse = self.api_call("conversations.history", request).await?;
+
+        Ok(response
+            .messages
+            .into_iter()
+            .find(|message| message.text.contains(pattern))
+            .map(|message| message.ts))
+    }
+
+    async fn post_message(
+        &self,
+        method: &str,
+        message: &slack_message::StandupMessage,
+    ) -> Result<slack_message::MessageResponse, Error> {
+        if self.dry_run {
+            print_dry_run(
+                &format!("Would POST {}/{}", self.base_url, method),
+                &serde_json::to_string_pretty(message).map_err(Error::slack)?,
+            );
+            return Ok(slack_message::MessageResponse::default());
+        }
+
+        let request = self.client
+            .post(format!("{}/{}", self.base_url, method))
+            .json(message)
+            .headers(self.headers.clone());
+        let response = self.api_call(method, request).await?;
+        println!("{}", "Message sent to Slack".green());
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackApi {
+    fn name(&self) -> &'static str {
+        "Slack"
+    }
+
+    // Post (or with --update edit) the stand-up and remember where it went
+    async fn send(&self, stand_up: &StandUp<'_>) -> Result<(), Error> {
+        let channel = self.channel.clone().ok_or_else(|| {
+            Error::config("slack.channel", "set the channel id to post the stand-up to, or the SLACK_CHANNEL env var")
+        })?;
+        let posts_file = self.posts_file.clone().ok_or_else(|| {
+            Error::config("files.slack_posts_json", "could not find the data directory, set it explicitly")
+        })?;
+        let mut post_log = PostLog::load(&posts_file)?;
+        let key = stand_up.date.format("%Y-%m-%d").to_string();
+        let mut message = StandupMessage::new(
+            channel.clone(),
+            stand_up.text.to_string(),
+            stand_up.context,
+            stand_up.custom_template,
+        );
+
+        if self.options.update {
+            let post = post_log.posts.get(&key).ok_or_else(|| Error::Validation(format!(
+                "No stand-up for {} was posted to Slack from here, run without --update to post it",
+                key
+            )))?;
+            message.channel = post.channel.clone();
+            message.ts = Some(post.ts.clone());
+            self.update_message(message).await?;
+            return Ok(());
+        }
+
+        message.thread_ts = match (&self.options.thread_ts, &self.thread_pattern) {
+            (Some(thread_ts), _) => Some(thread_ts.clone()),
+            (None, Some(pattern)) => {
+                let thread_ts = self
+                    .find_thread(&channel, pattern, calendar::start_of_day(stand_up.date))
+                    .await?;
+                if thread_ts.is_none() {
+                    println!("{}", format!("No thread matching `{}` found, posting to the channel", pattern).yellow());
+                }
+                thread_ts
+            }
+            (None, None) => None,
+        };
+        if let Some(post_at) = self.options.post_at {
+            message.post_at = Some(post_at.timestamp());
+            let response = self.schedule_message(message).await?;
+            if !response.scheduled_message_id.is_empty() {
+                println!(
+                    "{}    {}",
+                    format!("Scheduled for {}", post_at.format("%a %Y-%m-%d %H:%M")).green(),
+                    response.scheduled_message_id.blue()
+                );
+                post_log.scheduled.push(Scheduled {
+                    id: response.scheduled_message_id,
+                    channel: response.channel,
+                    post_at: response.post_at,
+                    date: key,
+                });
+                post_log.save(&posts_file)?;
+            }
+            return Ok(());
+        }
+
+        let thread_ts = message.thread_ts.clone();
+        let response = self.send_message(message).await?;
+        if let (Some(channel), Some(ts)) = (response.channel, response.ts) {
+            post_log.posts.insert(key, Post { channel, ts, thread_ts });
+            post_log.save(&posts_file)?;
+        }
         Ok(())
     }
 }
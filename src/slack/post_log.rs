@@ -0,0 +1,46 @@
+use crate::prelude::*;
+use crate::json_file::{load_json, save_json};
+use std::collections::BTreeMap;
+
+// Where a stand-up ended up, `channel` is the id Slack answered with
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Post {
+    pub channel: String,
+    pub ts: String,
+    pub thread_ts: Option<String>,
+}
+
+// A stand-up Slack will post later, `date` is the stand-up's YYYY-MM-DD
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Scheduled {
+    pub id: String,
+    pub channel: String,
+    pub post_at: i64,
+    pub date: String,
+}
+
+// Posted stand-ups keyed by their YYYY-MM-DD date, so `--update` can edit them,
+// and the ones waiting for `--at`
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct PostLog {
+    #[serde(default)]
+    pub posts: BTreeMap<String, Post>,
+    #[serde(default)]
+    pub scheduled: Vec<Scheduled>,
+}
+
+impl PostLog {
+    // A missing file is an empty log, nothing has been posted yet
+    pub fn load(file_path: &str) -> Result<Self, Error> {
+        Ok(load_json(file_path)?.unwrap_or_default())
+    }
+
+    // Slack has posted everything scheduled before `now`, so only the rest is pending
+    pub fn pending(&self, now: i64) -> Vec<&Scheduled> {
+        self.scheduled.iter().filter(|scheduled| scheduled.post_at > now).collect()
+    }
+
+    pub fn save(&self, file_path: &str) -> Result<(), Error> {
+        save_json(file_path, self)
+    }
+}
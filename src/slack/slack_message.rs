@@ -1,9 +1,41 @@
 use crate::prelude::*;
+use stand_up::{StandupContext, TaskGroup};
 
-#[derive(Debug, Deserialize, Serialize)]
+// Slack rejects section text longer than this
+const SECTION_TEXT_LIMIT: usize = 3000;
+// and messages with more blocks than this
+const MAX_BLOCKS: usize = 50;
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct MessageResponse {
-    pub ok: bool,
-    pub error: Option<String>,
+    pub channel: Option<String>,
+    pub ts: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ScheduleResponse {
+    pub channel: String,
+    pub scheduled_message_id: String,
+    pub post_at: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteScheduled {
+    pub channel: String,
+    pub scheduled_message_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryResponse {
+    #[serde(default)]
+    pub messages: Vec<HistoryMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryMessage {
+    pub ts: String,
+    #[serde(default)]
+    pub text: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -12,39 +44,206 @@ pub struct StandupMessage {
     pub channel: String,
     pub as_user: bool,
     pub mrkdwn: bool,
+    // plain-text fallback for notifications and clients without Block Kit
     pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_ts: Option<String>,
+    // only set for chat.update, the message being replaced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ts: Option<String>,
+    // only set for chat.scheduleMessage, unix seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_at: Option<i64>,
 }
 
 impl StandupMessage {
-    pub fn new(channel: String, text: String) -> Self {
-        let block = Block {
-            block_type: "section".to_string(),
-            text: Message {
-                text: text.clone(),
-                message_type: "mrkdwn".to_string()
-            }
+    // A custom standup.template is posted as it was rendered, the default one gets the Block Kit layout
+    pub fn new(channel: String, text: String, context: &StandupContext, custom_template: bool) -> Self {
+        let blocks = if custom_template {
+            template_blocks(&text)
+        } else {
+            standup_blocks(context)
         };
         Self {
-            blocks: vec![block],
+            blocks,
             channel,
             as_user: true,
             mrkdwn: true,
-            text
+            text: escape(&text),
+            thread_ts: None,
+            ts: None,
+            post_at: None,
         }
     }
-    
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-pub struct Block {
-    #[serde(rename = "type")]
-    pub block_type: String,
-    pub text: Message
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Block {
+    Header {
+        text: Message,
+    },
+    Section {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        text: Option<Message>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        fields: Option<Vec<Message>>,
+    },
+    Context {
+        elements: Vec<Message>,
+    },
+    Divider,
+}
+
+impl Block {
+    pub fn section(text: String) -> Self {
+        Block::Section {
+            text: Some(Message::Mrkdwn { text }),
+            fields: None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-pub struct Message {
-    pub text: String,
-    #[serde(rename = "type")]
-    pub message_type: String,
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    PlainText { text: String },
+    Mrkdwn { text: String },
+}
+
+// Header, then Today/Tomorrow/Blocker sections separated by dividers.
+// When a label per project doesn't fit in Slack's block limit the labels move into the lists
+fn standup_blocks(context: &StandupContext) -> Vec<Block> {
+    let blocks = layout(context, false);
+    if blocks.len() <= MAX_BLOCKS {
+        return blocks;
+    }
+    cap(layout(context, true))
+}
+
+fn layout(context: &StandupContext, compact: bool) -> Vec<Block> {
+    let blockers: Vec<String> = context.blockers.iter().map(|blocker| format!(" • {}", escape(blocker))).collect();
+    let mut blocks = vec![header(context)];
+    blocks.push(Block::Divider);
+    blocks.extend(group_blocks("Today", &context.today_groups, compact));
+    blocks.push(Block::Divider);
+    blocks.extend(group_blocks("Tomorrow", &context.tomorrow_groups, compact));
+    blocks.push(Block::Divider);
+    blocks.push(Block::section("*Blocker*".to_string()));
+    blocks.extend(list_sections(&blockers));
+    blocks
+}
+
+// The rendered template as it is, split into sections at its lines
+fn template_blocks(text: &str) -> Vec<Block> {
+    let lines: Vec<String> = text.lines().map(escape).collect();
+    cap(list_sections(&lines))
+}
+
+fn header(context: &StandupContext) -> Block {
+    Block::Header {
+        text: Message::PlainText {
+            text: format!("Stand-up {}", context.date),
+        },
+    }
+}
+
+// A context label above each project's tasks, or below the list naming every project when ungrouped.
+// Compact puts the labels in the list itself, so each title takes a single section
+fn group_blocks(title: &str, groups: &[TaskGroup], compact: bool) -> Vec<Block> {
+    let mut blocks = vec![Block::section(format!("*{}*", title))];
+    if groups.iter().all(|group| group.tasks.is_empty()) {
+        blocks.extend(list_sections(&[]));
+        return blocks;
+    }
+    let mut compact_lines = Vec::new();
+    for group in groups {
+        let lines: Vec<String> = group.tasks.iter().map(|task| format!(" • {}", escape(&task.label()))).collect();
+        let label = match &group.project {
+            Some(project) => Some(escape(project)),
+            None => {
+                let mut projects: Vec<String> = group.tasks.iter().flat_map(|task| task.projects.clone()).collect();
+                projects.sort();
+                projects.dedup();
+                Some(escape(&projects.join(" · "))).filter(|projects| !projects.is_empty())
+            }
+        };
+        if compact {
+            match (&group.project, label) {
+                (Some(_), Some(label)) => {
+                    compact_lines.push(format!("_{}_", label));
+                    compact_lines.extend(lines);
+                }
+                (_, label) => {
+                    compact_lines.extend(lines);
+                    compact_lines.extend(label.map(|label| format!("_{}_", label)));
+                }
+            }
+            continue;
+        }
+        match (&group.project, label) {
+            (Some(_), Some(label)) => {
+                blocks.push(project_label(label));
+                blocks.extend(list_sections(&lines));
+            }
+            (_, label) => {
+                blocks.extend(list_sections(&lines));
+                blocks.extend(label.map(project_label));
+            }
+        }
+    }
+    if compact {
+        blocks.extend(list_sections(&compact_lines));
+    }
+    blocks
+}
+
+// Cut what doesn't fit in one message, the text fallback still has all of it
+fn cap(mut blocks: Vec<Block>) -> Vec<Block> {
+    if blocks.len() > MAX_BLOCKS {
+        blocks.truncate(MAX_BLOCKS - 1);
+        blocks.push(project_label("_The stand-up is too long for Slack, the rest is cut off_".to_string()));
+    }
+    blocks
+}
+
+// Slack reads &, < and > in mrkdwn as markup
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn project_label(text: String) -> Block {
+    Block::Context {
+        elements: vec![Message::Mrkdwn { text }],
+    }
+}
+
+// Lines packed into as few sections as the text limit allows
+fn list_sections(lines: &[String]) -> Vec<Block> {
+    if lines.is_empty() {
+        return vec![Block::section(" • None".to_string())];
+    }
+    let mut sections = Vec::new();
+    let mut current = String::new();
+    for line in lines {
+        let line = truncate(line);
+        if !current.is_empty() && current.chars().count() + 1 + line.chars().count() > SECTION_TEXT_LIMIT {
+            sections.push(Block::section(std::mem::take(&mut current)));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(&line);
+    }
+    sections.push(Block::section(current));
+    sections
+}
+
+fn truncate(line: &str) -> String {
+    if line.chars().count() <= SECTION_TEXT_LIMIT {
+        return line.to_string();
+    }
+    let mut truncated: String = line.chars().take(SECTION_TEXT_LIMIT - 1).collect();
+    truncated.push('…');
+    truncated
 }
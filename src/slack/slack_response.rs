@@ -0,0 +1,51 @@
+use crate::prelude::*;
+use crate::notion::response::{backoff, retry_after};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+
+const MAX_RETRIES: u32 = 3;
+
+// Every Slack Web API answer carries `ok`, and `error` when it is false
+#[derive(Deserialize, Debug)]
+pub struct ErrorResponse {
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+impl SlackApi {
+    // Send a request to `method`, waiting out rate limits, and turn `ok: false` into an error
+    pub async fn api_call<T: DeserializeOwned>(&self, method: &str, request: RequestBuilder) -> Result<T, Error> {
+        let response = self.send_with_retry(request).await?;
+        let status = response.status();
+        let body = response.text().await.map_err(Error::slack)?;
+
+        match serde_json::from_str::<ErrorResponse>(&body) {
+            Ok(ErrorResponse { ok: true, .. }) => {}
+            Ok(ErrorResponse { error, .. }) => {
+                return Err(Error::SlackResponse {
+                    method: method.to_string(),
+                    error: error.unwrap_or_else(|| status.to_string()),
+                });
+            }
+            Err(_) => return Err(Error::slack(format!("{} {}", status, body))),
+        }
+        serde_json::from_str::<T>(&body).map_err(|e| Error::slack(format!("unexpected response: {}", e)))
+    }
+
+    async fn send_with_retry(&self, request: RequestBuilder) -> Result<Response, Error> {
+        let mut attempt = 0;
+        loop {
+            let retry = request
+                .try_clone()
+                .ok_or_else(|| Error::slack("request body can't be retried"))?;
+            let response = retry.send().await.map_err(Error::slack)?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt >= MAX_RETRIES {
+                return Ok(response);
+            }
+            let wait = retry_after(&response).unwrap_or_else(|| backoff(attempt));
+            println!("{}", format!("Slack is rate limiting, retrying in {:?}", wait).yellow());
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+}
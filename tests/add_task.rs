@@ -0,0 +1,160 @@
+mod common;
+
+use common::{stderr, stdout, TestEnv};
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn posts_task_to_add_with_mapped_project() {
+    let env = TestEnv::new().await;
+    env.write_project_cache(&[("Automate-Me", "project1-id")]);
+
+    Mock::given(method("POST"))
+        .and(path("/pages"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "page",
+            "id": "new-page",
+            "url": "https://www.notion.so/new-page"
+        })))
+        .mount(&env.server)
+        .await;
+
+    let output = env
+        .run(&["add-task", "-t", "Write tests", "-s", "in progress", "-p", "Automate-Me"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Write tests"));
+    assert!(stdout(&output).contains("https://www.notion.so/new-page"));
+
+    let requests = env.requests_to("/pages").await;
+    assert_eq!(requests.len(), 1);
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["parent"], json!({ "database_id": "task-db" }));
+    assert_eq!(body["properties"]["Name"]["title"][0]["text"]["content"], "Write tests");
+    assert_eq!(body["properties"]["Status"]["status"]["name"], "In progress");
+    assert_eq!(
+        body["properties"]["Projects"],
+        json!({ "type": "relation", "relation": [{ "id": "project1-id" }], "has_more": false })
+    );
+}
+
+async fn env_with_projects() -> TestEnv {
+    let env = TestEnv::new().await;
+    env.write_project_cache(&[("Automate-Me", "project1-id"), ("Blog", "project2-id")]);
+    env
+}
+
+#[tokio::test]
+async fn unknown_project_is_a_validation_error() {
+    let env = env_with_projects().await;
+
+    let output = env.run(&["add-task", "-t", "Write tests", "-p", "nope"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("Unknown project `nope`"));
+    assert!(stderr(&output).contains("projects list"));
+    assert!(env.requests_to("/pages").await.is_empty());
+}
+
+#[tokio::test]
+async fn unknown_status_is_a_validation_error() {
+    let env = env_with_projects().await;
+
+    let output = env.run(&["add-task", "-t", "Write tests", "-s", "later", "-p", "blog"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("Unknown status `later`"));
+}
+
+#[tokio::test]
+async fn blocked_is_the_status_named_by_standup_blocked_status() {
+    let mut env = env_with_projects().await;
+    Mock::given(method("POST"))
+        .and(path("/pages"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "page",
+            "id": "new-page",
+            "url": "https://www.notion.so/new-page"
+        })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["add-task", "-t", "Deploy API", "-s", "blocked", "-p", "blog"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("set standup.blocked_status"));
+
+    env.env("STANDUP_BLOCKED_STATUS", "On hold");
+    let output = env.run(&["add-task", "-t", "Deploy API", "-s", "blocked", "-p", "blog"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let body: Value = env.requests_to("/pages").await[0].body_json().unwrap();
+    assert_eq!(body["properties"]["Status"]["status"]["name"], "On hold");
+}
+
+#[tokio::test]
+async fn missing_env_var_is_a_config_error() {
+    let mut env = env_with_projects().await;
+    env.unset("NOTION_API_KEY");
+
+    let output = env.run(&["add-task", "-t", "Write tests", "-p", "blog"]).await;
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("set `notion.api_key`"));
+    assert!(stderr(&output).contains("NOTION_API_KEY"));
+}
+
+#[tokio::test]
+async fn notion_error_envelope_is_reported() {
+    let env = env_with_projects().await;
+    Mock::given(method("POST"))
+        .and(path("/pages"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "object": "error",
+            "status": 400,
+            "code": "validation_error",
+            "message": "Could not find page with ID: project2-id."
+        })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["add-task", "-t", "Write tests", "-p", "blog"]).await;
+    assert_eq!(output.status.code(), Some(5));
+    assert!(stderr(&output).contains("validation_error: Could not find page with ID: project2-id."));
+    assert!(stderr(&output).contains("run `automate-me projects sync`"));
+    assert!(!stdout(&output).contains("Write tests"));
+}
+
+#[tokio::test]
+async fn rate_limited_request_is_retried_after_delay() {
+    let env = env_with_projects().await;
+    Mock::given(method("POST"))
+        .and(path("/pages"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&env.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/pages"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "page",
+            "id": "new-page",
+            "url": "https://www.notion.so/new-page"
+        })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["add-task", "-t", "Write tests", "-p", "blog"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(env.requests_to("/pages").await.len(), 2);
+}
+
+#[tokio::test]
+async fn missing_arguments_outside_a_terminal_is_a_validation_error() {
+    let env = env_with_projects().await;
+
+    let output = env.run(&["add-task", "-t", "Write tests"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("--task and --project are required"));
+
+    let output = env.run(&["add-task", "-i"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(env.requests_to("/pages").await.is_empty());
+}
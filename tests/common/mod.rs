@@ -0,0 +1,235 @@
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::process::{Output, Stdio};
+use tempfile::TempDir;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use wiremock::MockServer;
+
+// Runs the real binary in a scratch directory with every client pointed at `server`
+pub struct TestEnv {
+    pub dir: TempDir,
+    pub server: MockServer,
+    envs: Vec<(String, String)>,
+}
+
+impl TestEnv {
+    pub async fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let server = MockServer::start().await;
+        let envs = vec![
+            ("NOTION_API_URL", server.uri()),
+            ("NOTION_API_KEY", "notion-key".to_string()),
+            ("NOTION_TASK_DATABASE_ID", "task-db".to_string()),
+            ("NOTION_READING_LIST_DATABASE_ID", "reading-db".to_string()),
+            ("NOTION_PROJECTS_DATABASE_ID", "projects-db".to_string()),
+            ("SLACK_API_URL", server.uri()),
+            ("SLACK_USER_TOKEN", "slack-token".to_string()),
+            ("SLACK_CHANNEL", "standup".to_string()),
+            ("SHEETS_API_URL", server.uri()),
+            ("GOOGLE_ACCESS_TOKEN", "sheets-token".to_string()),
+            ("SHEET_ID", "sheet-id".to_string()),
+            ("HOME", dir.path().display().to_string()),
+            ("XDG_CONFIG_HOME", dir.path().join("config").display().to_string()),
+            ("XDG_CACHE_HOME", dir.path().join("cache").display().to_string()),
+            ("XDG_DATA_HOME", dir.path().join("data").display().to_string()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+        Self { dir, server, envs }
+    }
+
+    pub fn env(&mut self, key: &str, value: &str) -> &mut Self {
+        self.envs.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn unset(&mut self, key: &str) -> &mut Self {
+        self.envs.retain(|(existing, _)| existing != key);
+        self
+    }
+
+    pub fn write_file(&self, name: &str, contents: &str) -> String {
+        let path = self.dir.path().join(name);
+        std::fs::write(&path, contents).unwrap();
+        path.display().to_string()
+    }
+
+    // Writes $XDG_CONFIG_HOME/automate-me/config.toml
+    pub fn write_config(&self, contents: &str) -> String {
+        let dir = self.dir.path().join("config").join("automate-me");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        std::fs::write(&path, contents).unwrap();
+        path.display().to_string()
+    }
+
+    // Writes $XDG_CACHE_HOME/automate-me/projects.json as `projects sync` would
+    pub fn write_project_cache(&self, projects: &[(&str, &str)]) {
+        let dir = self.dir.path().join("cache").join("automate-me");
+        std::fs::create_dir_all(&dir).unwrap();
+        let projects: Vec<serde_json::Value> = projects
+            .iter()
+            .map(|(name, id)| serde_json::json!({ "id": id, "name": name }))
+            .collect();
+        let cache = serde_json::json!({ "synced_at": "2026-10-01T09:00:00+00:00", "projects": projects });
+        std::fs::write(dir.join("projects.json"), cache.to_string()).unwrap();
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub async fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_automate-me"))
+            .args(args)
+            .current_dir(self.dir.path())
+            .env_clear()
+            .envs(self.envs.iter().map(|(key, value)| (key, value)))
+            .output()
+            .await
+            .unwrap()
+    }
+
+    pub async fn run_with_stdin(&self, args: &[&str], input: &str) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_automate-me"))
+            .args(args)
+            .current_dir(self.dir.path())
+            .env_clear()
+            .envs(self.envs.iter().map(|(key, value)| (key, value)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(input.as_bytes()).await.unwrap();
+        drop(stdin);
+        child.wait_with_output().await.unwrap()
+    }
+
+    pub async fn requests_to(&self, path: &str) -> Vec<wiremock::Request> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|request| request.url.path() == path)
+            .collect()
+    }
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+pub fn task(name: &str, status: &str) -> serde_json::Value {
+    serde_json::json!({
+        "object": "page",
+        "id": format!("{}-id", name),
+        "properties": {
+            "Name": {
+                "title": [{ "plain_text": name, "text": { "content": name } }]
+            },
+            "Status": { "status": { "name": status } },
+            "Projects": { "type": "relation", "relation": [{ "id": "project1-id" }], "has_more": false }
+        }
+    })
+}
+
+// The task database answers every query with `tasks`, in a single page
+pub async fn mock_tasks(env: &TestEnv, tasks: Vec<serde_json::Value>) {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    Mock::given(method("POST"))
+        .and(path("/databases/task-db/query"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "results": tasks,
+            "has_more": false,
+            "next_cursor": null
+        })))
+        .mount(&env.server)
+        .await;
+}
+
+pub const TIMELOG_HEADER: &[&str] = &["Date", "In Office", "Task", "hrs"];
+
+pub fn timelog_sheet_name() -> String {
+    use chrono::prelude::*;
+    let today = Local::now();
+    format!("{}({})", today.format("%b"), today.year())
+}
+
+// `header` and a row per day of the month up to today, today's row holding `row` after its date
+pub fn month_rows(header: &[&str], row: &[&str]) -> serde_json::Value {
+    use chrono::prelude::*;
+    let today = Local::now().date_naive();
+    let mut rows = vec![serde_json::json!(header)];
+    for day in 1..=today.day() {
+        let date = today.with_day(day).unwrap().format("%m/%d/%Y").to_string();
+        let mut cells = vec![date];
+        if day == today.day() {
+            cells.extend(row.iter().map(|cell| cell.to_string()));
+        }
+        rows.push(serde_json::json!(cells));
+    }
+    serde_json::json!(rows)
+}
+
+// This month's timelog sheet exists with the default columns and today's row holds `row` (In Office, Task, hrs)
+pub async fn mock_timelog_sheet(env: &TestEnv, row: &[&str]) -> String {
+    mock_timelog_values(env, month_rows(TIMELOG_HEADER, row)).await
+}
+
+// This month's timelog sheet exists and holds `values`, writes to it succeed
+pub async fn mock_timelog_values(env: &TestEnv, values: serde_json::Value) -> String {
+    use wiremock::matchers::{method, path, path_regex};
+    use wiremock::{Mock, ResponseTemplate};
+
+    let sheet_name = timelog_sheet_name();
+    Mock::given(method("GET"))
+        .and(path("/v4/spreadsheets/sheet-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "spreadsheetId": "sheet-id",
+            "sheets": [{ "properties": { "sheetId": 7, "title": sheet_name } }]
+        })))
+        .mount(&env.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path_regex("^/v4/spreadsheets/sheet-id/values/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "majorDimension": "ROWS",
+            "values": values
+        })))
+        .mount(&env.server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path_regex("^/v4/spreadsheets/sheet-id/values/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "spreadsheetId": "sheet-id" })))
+        .mount(&env.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v4/spreadsheets/sheet-id:batchUpdate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "spreadsheetId": "sheet-id" })))
+        .mount(&env.server)
+        .await;
+    sheet_name
+}
+
+// Updated and appended timelog rows
+pub async fn timelog_writes(env: &TestEnv) -> Vec<wiremock::Request> {
+    env.server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|request| request.method.as_str() == "PUT" || request.url.path().ends_with(":batchUpdate"))
+        .collect()
+}
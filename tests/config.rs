@@ -0,0 +1,213 @@
+mod common;
+
+use common::{mock_tasks, stderr, stdout, task, TestEnv};
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+async fn mock_notion_and_slack(env: &TestEnv) {
+    mock_tasks(env, vec![task("Fix login", "Done")]).await;
+    Mock::given(method("POST"))
+        .and(path("/chat.postMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+        .mount(&env.server)
+        .await;
+}
+
+async fn posted_channel(env: &TestEnv) -> Value {
+    let requests = env.requests_to("/chat.postMessage").await;
+    let body: Value = requests[0].body_json().unwrap();
+    body["channel"].clone()
+}
+
+#[tokio::test]
+async fn reads_settings_from_config_file() {
+    let mut env = TestEnv::new().await;
+    env.unset("SLACK_CHANNEL").unset("SLACK_USER_TOKEN");
+    env.write_config(
+        r#"
+[slack]
+user_token = "file-token"
+channel = "general"
+"#,
+    );
+    mock_notion_and_slack(&env).await;
+
+    let output = env.run(&["generate-stand-up", "--slack"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(posted_channel(&env).await, "general");
+    let requests = env.requests_to("/chat.postMessage").await;
+    assert_eq!(requests[0].headers["authorization"], "Bearer file-token");
+}
+
+#[tokio::test]
+async fn profile_overrides_file_and_env_overrides_profile() {
+    let mut env = TestEnv::new().await;
+    env.unset("SLACK_CHANNEL");
+    env.write_config(
+        r#"
+[slack]
+channel = "general"
+
+[profiles.work.slack]
+channel = "work-standup"
+"#,
+    );
+    mock_notion_and_slack(&env).await;
+
+    let output = env.run(&["generate-stand-up", "--slack", "--profile", "work"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(posted_channel(&env).await, "work-standup");
+
+    env.env("SLACK_CHANNEL", "from-env");
+    let output = env.run(&["--profile", "work", "generate-stand-up", "--slack"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let requests = env.requests_to("/chat.postMessage").await;
+    let body: Value = requests[1].body_json().unwrap();
+    assert_eq!(body["channel"], "from-env");
+}
+
+#[tokio::test]
+async fn unknown_profile_is_a_config_error() {
+    let env = TestEnv::new().await;
+    env.write_config("[profiles.work.slack]\nchannel = \"work\"\n");
+
+    let output = env.run(&["config", "show", "--profile", "home"]).await;
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("no profile named `home`"));
+    assert!(stderr(&output).contains("known profiles: work"));
+}
+
+#[tokio::test]
+async fn validate_reports_missing_settings_without_network_calls() {
+    let mut env = TestEnv::new().await;
+    env.unset("SLACK_CHANNEL").unset("SHEET_ID");
+
+    let output = env.run(&["config", "validate"]).await;
+    assert_eq!(output.status.code(), Some(2));
+    let report = stdout(&output);
+    assert!(report.contains("slack.channel is not set"));
+    assert!(report.contains("sheets.sheet_id is not set"));
+    assert!(report.contains("files.reading_list_csv is not set"));
+    assert!(!report.contains("notion.api_key is not set"));
+    assert!(env.server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn validate_passes_with_complete_config() {
+    let mut env = TestEnv::new().await;
+    let csv = env.write_file("reading_list.csv", "id,url,did_i_read_it,priority\n");
+    env.env("READING_LIST_CSV", &csv);
+    env.write_project_cache(&[]);
+
+    let output = env.run(&["config", "validate"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Config is valid"));
+}
+
+#[tokio::test]
+async fn validate_only_notes_a_missing_project_cache() {
+    let mut env = TestEnv::new().await;
+    let csv = env.write_file("reading_list.csv", "id,url,did_i_read_it,priority\n");
+    env.env("READING_LIST_CSV", &csv);
+
+    let output = env.run(&["config", "validate"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let report = stdout(&output);
+    assert!(report.contains("no project cache at"));
+    assert!(report.contains("run `automate-me projects sync`"));
+    assert!(report.contains("Config is valid"));
+}
+
+#[tokio::test]
+async fn numeric_settings_can_be_written_as_numbers() {
+    let env = TestEnv::new().await;
+    env.write_config("[notify]\nsmtp_host = \"mail.local\"\nsmtp_port = 2525\n");
+
+    let output = env.run(&["config", "show"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let port = stdout(&output).lines().find(|line| line.starts_with("notify.smtp_port")).unwrap().to_string();
+    assert!(port.contains("2525"), "{}", port);
+    assert!(port.contains("(file)"), "{}", port);
+}
+
+#[tokio::test]
+async fn list_settings_can_be_written_as_arrays() {
+    let env = TestEnv::new().await;
+    env.write_config("[notify]\ntargets = [\"slack\", \"teams\"]\nteams_webhook = \"https://teams.local/hook\"\n");
+
+    let output = env.run(&["config", "show"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let targets = stdout(&output).lines().find(|line| line.starts_with("notify.targets")).unwrap().to_string();
+    assert!(targets.contains("slack, teams"), "{}", targets);
+
+    let output = env.run(&["config", "validate"]).await;
+    assert!(!stdout(&output).contains("notify.targets"), "{:?}", output);
+}
+
+#[tokio::test]
+async fn validate_reports_timelog_columns_without_a_date_first() {
+    let env = TestEnv::new().await;
+    env.write_config(
+        r#"
+[[sheets.columns]]
+name = "Task"
+source = "task"
+
+[[sheets.columns]]
+name = "Date"
+source = "date"
+"#,
+    );
+
+    let output = env.run(&["config", "validate"]).await;
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout(&output).contains("sheets.columns"));
+    assert!(stdout(&output).contains("the first column must have source = \"date\""));
+}
+
+#[tokio::test]
+async fn show_masks_secrets_and_reports_sources() {
+    let mut env = TestEnv::new().await;
+    env.unset("SLACK_CHANNEL");
+    env.write_config("[slack]\nchannel = \"general\"\n");
+
+    let output = env.run(&["config", "show"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let shown = stdout(&output);
+    assert!(!shown.contains("notion-key"));
+    assert!(shown.contains("********"));
+    assert!(shown.contains("general"));
+    assert!(shown.contains("(file)"));
+    assert!(shown.contains("(env NOTION_API_KEY)"));
+}
+
+#[tokio::test]
+async fn init_writes_template_once() {
+    let env = TestEnv::new().await;
+
+    let output = env.run(&["config", "init"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let path = env.path().join("config").join("automate-me").join("config.toml");
+    let template = std::fs::read_to_string(&path).unwrap();
+    assert!(template.contains("[notion]"));
+    assert!(template.contains("task_database_id = \"\""));
+
+    let output = env.run(&["config", "init"]).await;
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--force"));
+}
+
+#[tokio::test]
+async fn init_force_replaces_a_config_that_does_not_parse() {
+    let env = TestEnv::new().await;
+    let path = env.write_config("[notion\napi_key = ");
+
+    let output = env.run(&["config", "show"]).await;
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = env.run(&["config", "init", "--force"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(std::fs::read_to_string(&path).unwrap().contains("[notion]"));
+    assert!(env.run(&["config", "show"]).await.status.success());
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use chrono::prelude::*;
+use common::{mock_tasks, mock_timelog_sheet, stdout, task, timelog_writes, TestEnv};
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn stand_up_prints_slack_message_and_timelog_row_without_sending() {
+    let env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    let today = Local::now();
+    let sheet_name = mock_timelog_sheet(&env, &[]).await;
+
+    let output = env
+        .run(&["--dry-run", "generate-stand-up", "--slack", "--timelog", "-i", "Office"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    let printed = stdout(&output);
+    assert!(printed.contains("Would POST"));
+    assert!(printed.contains("\"channel\": \"standup\""));
+    let row = today.day() + 1;
+    assert!(printed.contains(&format!("Would update {}!B{}:D{}", sheet_name, row, row)));
+    assert!(printed.contains("\"Office\""));
+    assert!(printed.contains("\" • Fix login\""));
+
+    assert!(env.requests_to("/chat.postMessage").await.is_empty());
+    assert!(timelog_writes(&env).await.is_empty());
+}
+
+#[tokio::test]
+async fn add_task_prints_the_page_instead_of_creating_it() {
+    let env = TestEnv::new().await;
+    env.write_project_cache(&[("Automate-Me", "project1-id")]);
+
+    let output = env
+        .run(&["add-task", "-t", "Write tests", "-p", "Automate-Me", "--dry-run"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    let printed = stdout(&output);
+    assert!(printed.contains("Would POST"));
+    assert!(printed.contains("\"content\": \"Write tests\""));
+    assert!(env.requests_to("/pages").await.is_empty());
+}
+
+#[tokio::test]
+async fn reading_list_is_not_rewritten() {
+    let mut env = TestEnv::new().await;
+    let contents = "id,url,did_i_read_it,priority\nfirst,https://notion.so/first,false,100\n";
+    let csv = env.write_file("reading_list.csv", contents);
+    env.env("READING_LIST_CSV", &csv);
+    Mock::given(method("POST"))
+        .and(path("/databases/reading-db/query"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "results": [{
+                "object": "page",
+                "id": "second",
+                "url": "https://notion.so/second",
+                "properties": { "Did I read it": { "checkbox": false } }
+            }],
+            "has_more": false,
+            "next_cursor": null
+        })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["--dry-run", "give-me-article", "--no-open"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let printed = stdout(&output);
+    assert!(printed.contains(" • second,https://notion.so/second,false,100"));
+    assert!(printed.contains(" • first,https://notion.so/first,false,99"));
+    assert_eq!(std::fs::read_to_string(&csv).unwrap(), contents);
+}
@@ -0,0 +1,691 @@
+mod common;
+
+use chrono::prelude::*;
+use common::{
+    mock_tasks, mock_timelog_sheet, mock_timelog_values, month_rows, stderr, stdout, task, timelog_sheet_name, timelog_writes, TestEnv,
+    TIMELOG_HEADER,
+};
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+async fn mock_standup_tasks(env: &TestEnv) {
+    mock_tasks(
+        env,
+        vec![task("Fix login", "Done"), task("Review PR", "In progress"), task("Plan sprint", "To Do")],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn queries_notion_with_todays_filter() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+
+    let output = env.run(&["generate-stand-up"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let stand_up = stdout(&output);
+    assert!(stand_up.contains("Today\n • Fix login\n • WIP Review PR\nTomorrow\n • Plan sprint\nBlocker\n • None"));
+
+    let requests = env.requests_to("/databases/task-db/query").await;
+    let body: Value = requests[0].body_json().unwrap();
+    let statuses: Vec<&Value> = body["filter"]["and"][0]["or"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|condition| &condition["status"]["equals"])
+        .collect();
+    assert_eq!(statuses, vec!["In progress", "Done", "To Do"]);
+    assert_eq!(
+        body["filter"]["and"][1],
+        json!({
+            "property": "Last edited time",
+            "last_edited_time": { "on_or_after": Local::now().format("%Y-%m-%d").to_string() }
+        })
+    );
+    assert_eq!(body["sorts"], json!([{ "property": "Last edited time", "direction": "ascending" }]));
+}
+
+#[tokio::test]
+async fn sends_standup_message_to_slack() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    Mock::given(method("POST"))
+        .and(path("/chat.postMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["generate-stand-up", "--slack"]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let requests = env.requests_to("/chat.postMessage").await;
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].headers["authorization"], "Bearer slack-token");
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["channel"], "standup");
+    assert_eq!(body["as_user"], true);
+    assert!(body["text"].as_str().unwrap().starts_with("Stand-up "));
+    let date = body["text"].as_str().unwrap().lines().next().unwrap();
+    assert_eq!(
+        body["blocks"],
+        json!([
+            { "type": "header", "text": { "type": "plain_text", "text": date } },
+            { "type": "divider" },
+            { "type": "section", "text": { "type": "mrkdwn", "text": "*Today*" } },
+            { "type": "section", "text": { "type": "mrkdwn", "text": " • Fix login\n • WIP Review PR" } },
+            { "type": "divider" },
+            { "type": "section", "text": { "type": "mrkdwn", "text": "*Tomorrow*" } },
+            { "type": "section", "text": { "type": "mrkdwn", "text": " • Plan sprint" } },
+            { "type": "divider" },
+            { "type": "section", "text": { "type": "mrkdwn", "text": "*Blocker*" } },
+            { "type": "section", "text": { "type": "mrkdwn", "text": " • None" } }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn slack_blocks_label_projects_and_split_long_lists() {
+    let env = TestEnv::new().await;
+    env.write_project_cache(&[("Automate Me", "project1-id")]);
+    let name = "x".repeat(900);
+    let tasks: Vec<Value> = (0..4).map(|i| task(&format!("{} {}", name, i), "Done")).collect();
+    mock_tasks(&env, tasks).await;
+    Mock::given(method("POST"))
+        .and(path("/chat.postMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["generate-stand-up", "--slack"]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let requests = env.requests_to("/chat.postMessage").await;
+    let body: Value = requests[0].body_json().unwrap();
+    let blocks = body["blocks"].as_array().unwrap();
+    assert_eq!(
+        blocks[3],
+        json!({ "type": "context", "elements": [{ "type": "mrkdwn", "text": "Automate Me" }] })
+    );
+    assert_eq!(blocks[4]["text"]["text"].as_str().unwrap().lines().count(), 3);
+    assert_eq!(blocks[5]["text"]["text"].as_str().unwrap().lines().count(), 1);
+    assert!(blocks
+        .iter()
+        .filter_map(|block| block["text"]["text"].as_str())
+        .all(|text| text.chars().count() <= 3000));
+    assert!(body["text"].as_str().unwrap().contains("*Automate Me*"));
+}
+
+async fn mock_slack_post(env: &TestEnv) {
+    Mock::given(method("POST"))
+        .and(path("/chat.postMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+        .mount(&env.server)
+        .await;
+}
+
+#[tokio::test]
+async fn slack_blocks_escape_task_names() {
+    let env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix <login> & co", "Done")]).await;
+    mock_slack_post(&env).await;
+
+    let output = env.run(&["generate-stand-up", "--slack", "--no-group"]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let body: Value = env.requests_to("/chat.postMessage").await[0].body_json().unwrap();
+    assert_eq!(body["blocks"][3]["text"]["text"], " • Fix &lt;login&gt; &amp; co");
+    assert!(body["text"].as_str().unwrap().contains(" • Fix &lt;login&gt; &amp; co"));
+}
+
+#[tokio::test]
+async fn slack_blocks_keep_within_the_block_limit() {
+    let env = TestEnv::new().await;
+    let projects: Vec<(String, String)> = (0..30)
+        .map(|i| (format!("Project {}", i), format!("project{}-id", i)))
+        .collect();
+    env.write_project_cache(
+        &projects
+            .iter()
+            .map(|(name, id)| (name.as_str(), id.as_str()))
+            .collect::<Vec<_>>(),
+    );
+    let tasks: Vec<Value> = (0..30)
+        .map(|i| {
+            let mut task = task(&format!("Task {}", i), "Done");
+            task["properties"]["Projects"]["relation"] = json!([{ "id": format!("project{}-id", i) }]);
+            task
+        })
+        .collect();
+    mock_tasks(&env, tasks).await;
+    mock_slack_post(&env).await;
+
+    let output = env.run(&["generate-stand-up", "--slack"]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let body: Value = env.requests_to("/chat.postMessage").await[0].body_json().unwrap();
+    let blocks = body["blocks"].as_array().unwrap();
+    assert!(blocks.len() <= 50, "{} blocks", blocks.len());
+    let today = blocks[3]["text"]["text"].as_str().unwrap();
+    assert!(today.starts_with("_Project 0_\n • Task 0\n_Project 1_\n • Task 1"));
+    assert!(today.contains("_Project 29_\n • Task 29"));
+}
+
+#[tokio::test]
+async fn slack_posts_a_custom_template_as_rendered() {
+    let mut env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    mock_slack_post(&env).await;
+    let template = env.write_file(
+        "standup.hbs",
+        "*Done*\n{{#each today}}- {{name}}\n{{/each}}*Next*\n{{#each tomorrow}}- {{name}}\n{{/each}}",
+    );
+    env.env("STANDUP_TEMPLATE", &template);
+
+    let output = env.run(&["generate-stand-up", "--slack"]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let body: Value = env.requests_to("/chat.postMessage").await[0].body_json().unwrap();
+    assert_eq!(
+        body["blocks"],
+        json!([
+            { "type": "section", "text": { "type": "mrkdwn", "text": "*Done*\n- Fix login\n- Review PR\n*Next*\n- Plan sprint" } }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn updates_todays_row_in_existing_timelog_sheet() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    let today = Local::now();
+    let sheet_name = mock_timelog_sheet(&env, &[]).await;
+
+    let output = env
+        .run(&["generate-stand-up", "--timelog", "-i", "Office", "-w", "6"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let requests = timelog_writes(&env).await;
+    assert_eq!(requests.len(), 1);
+    let row = today.day() + 1;
+    let range = format!("{}!B{}:D{}", sheet_name, row, row);
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["range"], range);
+    assert_eq!(body["values"], json!([["Office", " • Fix login\n • WIP Review PR", "6"]]));
+    assert!(requests[0]
+        .url
+        .query_pairs()
+        .any(|(key, value)| key == "valueInputOption" && value == "USER_ENTERED"));
+}
+
+#[tokio::test]
+async fn append_keeps_manual_notes_without_duplicating_tasks() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    mock_timelog_sheet(&env, &["WFH", "Call with client\n • Fix login", ""]).await;
+
+    let output = env.run(&["generate-stand-up", "--timelog", "-w", "6"]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let requests = timelog_writes(&env).await;
+    assert_eq!(requests.len(), 1);
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(
+        body["values"],
+        json!([["WFH", "Call with client\n • Fix login\n • WIP Review PR", "6"]])
+    );
+}
+
+#[tokio::test]
+async fn append_adds_grouped_tasks_under_their_project_heading() {
+    let env = TestEnv::new().await;
+    mock_tasks_in_two_projects(&env).await;
+    env.write_project_cache(&[("Automate Me", "project1-id"), ("Website", "project2-id")]);
+    mock_timelog_sheet(&env, &["WFH", "Automate Me\n • Fix login\nWebsite\n • Fix login\nCall with client", ""]).await;
+
+    let output = env.run(&["generate-stand-up", "--timelog", "-w", "6"]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let body: Value = timelog_writes(&env).await[0].body_json().unwrap();
+    assert_eq!(
+        body["values"][0][1],
+        "Automate Me\n • Fix login\n • WIP Review PR\nWebsite\n • Fix login\n • Write docs\nCall with client"
+    );
+}
+
+#[tokio::test]
+async fn append_matches_tasks_logged_with_and_without_project_headings() {
+    let env = TestEnv::new().await;
+    mock_tasks_in_two_projects(&env).await;
+    env.write_project_cache(&[("Automate Me", "project1-id"), ("Website", "project2-id")]);
+    mock_timelog_sheet(&env, &["WFH", " • Fix login\n • WIP Review PR", ""]).await;
+
+    let output = env.run(&["generate-stand-up", "--timelog", "-w", "6"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let body: Value = timelog_writes(&env).await[0].body_json().unwrap();
+    assert_eq!(body["values"][0][1], "• Fix login\n • WIP Review PR\nWebsite\n • Write docs");
+
+    let env = TestEnv::new().await;
+    mock_tasks_in_two_projects(&env).await;
+    env.write_project_cache(&[("Automate Me", "project1-id"), ("Website", "project2-id")]);
+    mock_timelog_sheet(&env, &["WFH", "Automate Me\n • Fix login\n • WIP Review PR\nWebsite\n • Write docs", "6"]).await;
+
+    let output = env.run(&["generate-stand-up", "--timelog", "-w", "6", "--no-group"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(timelog_writes(&env).await.is_empty());
+}
+
+#[tokio::test]
+async fn skip_if_filled_leaves_a_filled_row_alone() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    mock_timelog_sheet(&env, &["Office", "Written by hand", "8"]).await;
+
+    let output = env
+        .run(&["generate-stand-up", "--timelog", "--timelog-mode", "skip-if-filled"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("already filled"));
+    assert!(timelog_writes(&env).await.is_empty());
+}
+
+#[tokio::test]
+async fn overwrite_replaces_the_existing_row() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    mock_timelog_sheet(&env, &["Office", "Written by hand", "8"]).await;
+
+    let output = env
+        .run(&["generate-stand-up", "--timelog", "--timelog-mode", "overwrite", "-w", "6"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let requests = timelog_writes(&env).await;
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["values"], json!([["WFH", " • Fix login\n • WIP Review PR", "6"]]));
+}
+
+#[tokio::test]
+async fn finds_todays_row_by_its_date_in_a_sorted_sheet() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    let today = Local::now().date_naive().format("%m/%d/%Y").to_string();
+    mock_timelog_values(&env, json!([TIMELOG_HEADER, ["01/02/2020"], [today, "Office"], ["Total"]])).await;
+
+    let output = env.run(&["generate-stand-up", "--timelog"]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let requests = timelog_writes(&env).await;
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["range"], format!("{}!B3:D3", timelog_sheet_name()));
+    assert_eq!(body["values"][0][0], "Office");
+}
+
+#[tokio::test]
+async fn appends_a_row_when_the_sheet_has_none_for_today() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    mock_timelog_values(&env, json!([TIMELOG_HEADER, ["01/02/2020"]])).await;
+
+    let output = env.run(&["generate-stand-up", "--timelog", "-w", "6"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Added a timelog row"));
+
+    let requests = timelog_writes(&env).await;
+    assert_eq!(requests.len(), 1);
+    let body: Value = requests[0].body_json().unwrap();
+    let append = &body["requests"][0]["appendCells"];
+    assert_eq!(append["sheetId"], 7);
+    let row: Vec<&str> = append["rows"][0]["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|cell| cell["userEnteredValue"]["stringValue"].as_str().unwrap())
+        .collect();
+    let today = Local::now().date_naive().format("%m/%d/%Y").to_string();
+    assert_eq!(row, [today.as_str(), "WFH", " • Fix login\n • WIP Review PR", "6"]);
+}
+
+#[tokio::test]
+async fn rerun_after_an_append_updates_the_appended_row() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    // The appended date is text, so the sheet hands it back as it was written
+    let today = Local::now().date_naive().format("%m/%d/%Y").to_string();
+    mock_timelog_values(&env, json!([TIMELOG_HEADER, ["01/02/2020"], [today, "WFH", " • Fix login", "6"]])).await;
+
+    let output = env.run(&["generate-stand-up", "--timelog", "-w", "6"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!stdout(&output).contains("Added a timelog row"));
+
+    let requests = timelog_writes(&env).await;
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method.as_str(), "PUT");
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["range"], format!("{}!B3:D3", timelog_sheet_name()));
+    assert_eq!(body["values"], json!([["WFH", "• Fix login\n • WIP Review PR", "6"]]));
+}
+
+#[tokio::test]
+async fn sheet_with_another_layout_is_a_validation_error() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    mock_timelog_values(&env, month_rows(&["Day", "Notes"], &[])).await;
+
+    let output = env.run(&["generate-stand-up", "--timelog"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("expected the header `Date | In Office | Task | hrs` but found `Day | Notes`"));
+    assert!(timelog_writes(&env).await.is_empty());
+}
+
+const TIMESHEET_COLUMNS: &str = r#"
+[[sheets.columns]]
+name = "Date"
+source = "date"
+
+[[sheets.columns]]
+name = "Project"
+source = "projects"
+width = 160
+
+[[sheets.columns]]
+name = "Ticket"
+
+[[sheets.columns]]
+name = "Task"
+source = "task"
+width = 480
+
+[[sheets.columns]]
+name = "Billable"
+source = "text"
+value = "Yes"
+
+[[sheets.columns]]
+name = "hrs"
+source = "hours"
+"#;
+
+#[tokio::test]
+async fn timelog_row_follows_the_configured_columns() {
+    let env = TestEnv::new().await;
+    env.write_config(TIMESHEET_COLUMNS);
+    env.write_project_cache(&[("Automate Me", "project1-id")]);
+    mock_standup_tasks(&env).await;
+    let header = ["Date", "Project", "Ticket", "Task", "Billable", "hrs"];
+    let sheet_name = mock_timelog_values(&env, month_rows(&header, &["", "JIRA-12"])).await;
+
+    let output = env
+        .run(&["generate-stand-up", "--timelog", "--no-group", "-w", "6"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let requests = timelog_writes(&env).await;
+    let row = Local::now().day() + 1;
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["range"], format!("{}!B{}:F{}", sheet_name, row, row));
+    assert_eq!(
+        body["values"],
+        json!([["Automate Me", "JIRA-12", " • Fix login\n • WIP Review PR", "Yes", "6"]])
+    );
+}
+
+#[tokio::test]
+async fn new_timelog_sheet_gets_the_configured_header_and_widths() {
+    let env = TestEnv::new().await;
+    env.write_config(TIMESHEET_COLUMNS);
+    mock_standup_tasks(&env).await;
+    Mock::given(method("GET"))
+        .and(path("/v4/spreadsheets/sheet-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "spreadsheetId": "sheet-id", "sheets": [] })))
+        .mount(&env.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v4/spreadsheets/sheet-id:batchUpdate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "spreadsheetId": "sheet-id",
+            "replies": [{ "addSheet": { "properties": { "sheetId": 9, "title": "new" } } }]
+        })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["generate-stand-up", "--timelog"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Updated new sheet"));
+
+    let requests = env.requests_to("/v4/spreadsheets/sheet-id:batchUpdate").await;
+    assert_eq!(requests.len(), 2);
+    let body: Value = requests[1].body_json().unwrap();
+    let header: Vec<&str> = body["requests"][0]["updateCells"]["rows"][0]["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|cell| cell["userEnteredValue"]["stringValue"].as_str().unwrap())
+        .collect();
+    assert_eq!(header, ["Date", "Project", "Ticket", "Task", "Billable", "hrs"]);
+    assert_eq!(body["requests"][1]["updateCells"]["range"]["endColumnIndex"], 6);
+    let widths: Vec<(i64, i64)> = body["requests"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|request| request.get("updateDimensionProperties"))
+        .map(|update| {
+            (
+                update["range"]["startIndex"].as_i64().unwrap(),
+                update["properties"]["pixelSize"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(widths, [(1, 160), (3, 480)]);
+}
+
+#[tokio::test]
+async fn server_errors_are_retried_with_backoff() {
+    let env = TestEnv::new().await;
+    Mock::given(method("POST"))
+        .and(path("/databases/task-db/query"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&env.server)
+        .await;
+    mock_standup_tasks(&env).await;
+
+    let output = env.run(&["generate-stand-up"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Fix login"));
+    assert_eq!(env.requests_to("/databases/task-db/query").await.len(), 2);
+}
+
+#[tokio::test]
+async fn notion_error_object_is_not_a_decode_error() {
+    let env = TestEnv::new().await;
+    Mock::given(method("POST"))
+        .and(path("/databases/task-db/query"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "object": "error",
+            "status": 404,
+            "code": "object_not_found",
+            "message": "Could not find database with ID: task-db."
+        })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["generate-stand-up"]).await;
+    assert_eq!(output.status.code(), Some(5));
+    assert!(stderr(&output).contains("object_not_found: Could not find database with ID: task-db."));
+    assert!(stderr(&output).contains("shared with your integration"));
+}
+
+#[tokio::test]
+async fn renders_custom_template_with_project_names() {
+    let mut env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    env.write_project_cache(&[("Automate Me", "project1-id")]);
+    let template = env.write_file(
+        "standup.hbs",
+        "{{weekday}} {{iso_date}}\n*Yesterday*\n{{#each today}}- {{name}} ({{status}}) [{{projects.[0]}}]\n{{/each}}*Projects* {{#each projects}}{{this}}{{/each}}\n",
+    );
+    env.env("STANDUP_TEMPLATE", &template);
+
+    let output = env.run(&["generate-stand-up"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let stand_up = stdout(&output);
+    assert!(stand_up.contains("*Yesterday*\n- Fix login (Done) [Automate Me]\n- Review PR (In progress) [Automate Me]\n*Projects* Automate Me"));
+    assert!(stand_up.contains(&Local::now().format("%A %Y-%m-%d").to_string()));
+}
+
+#[tokio::test]
+async fn broken_template_fails_before_querying_notion() {
+    let mut env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    let template = env.write_file("standup.hbs", "{{#each today}}{{name}}");
+    env.env("STANDUP_TEMPLATE", &template);
+
+    let output = env.run(&["generate-stand-up"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("is invalid"));
+    assert!(env.requests_to("/databases/task-db/query").await.is_empty());
+}
+
+#[tokio::test]
+async fn blocked_tasks_and_blocker_flags_fill_the_blocker_section() {
+    let mut env = TestEnv::new().await;
+    env.env("STANDUP_BLOCKED_STATUS", "Blocked");
+    mock_tasks(&env, vec![task("Fix login", "Done"), task("Deploy API", "Blocked")]).await;
+
+    let output = env
+        .run(&["generate-stand-up", "-b", "Waiting on design review"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    let stand_up = stdout(&output);
+    assert!(stand_up.contains("Today\n • Fix login\nTomorrow\nBlocker\n • Deploy API\n • Waiting on design review"));
+    assert!(!stand_up.contains("None"));
+
+    let body: Value = env.requests_to("/databases/task-db/query").await[0].body_json().unwrap();
+    assert_eq!(body["filter"]["and"][0]["or"][3], json!({ "property": "Status", "status": { "equals": "Blocked" } }));
+}
+
+async fn mock_tasks_in_two_projects(env: &TestEnv) {
+    let mut docs = task("Write docs", "Done");
+    docs["properties"]["Projects"]["relation"] = json!([{ "id": "project2-id" }]);
+    mock_tasks(env, vec![task("Fix login", "Done"), docs, task("Review PR", "In progress")]).await;
+    Mock::given(method("GET"))
+        .and(path("/pages/project2id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "page",
+            "id": "project2-id",
+            "properties": {
+                "Project name": { "type": "title", "title": [{ "plain_text": "Website" }] }
+            }
+        })))
+        .mount(&env.server)
+        .await;
+}
+
+#[tokio::test]
+async fn groups_tasks_under_their_project() {
+    let env = TestEnv::new().await;
+    mock_tasks_in_two_projects(&env).await;
+    env.write_project_cache(&[("Automate Me", "project1-id")]);
+
+    let output = env.run(&["generate-stand-up"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains(
+        "Today\n*Automate Me*\n • Fix login\n • WIP Review PR\n*Website*\n • Write docs\nTomorrow"
+    ));
+    assert_eq!(env.requests_to("/pages/project2id").await.len(), 1);
+    assert!(env.requests_to("/pages/project1id").await.is_empty());
+}
+
+#[tokio::test]
+async fn no_group_keeps_a_flat_list() {
+    let env = TestEnv::new().await;
+    mock_tasks_in_two_projects(&env).await;
+    env.write_project_cache(&[("Automate Me", "project1-id")]);
+
+    let output = env.run(&["generate-stand-up", "--no-group"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Today\n • Fix login\n • Write docs\n • WIP Review PR\nTomorrow"));
+}
+
+#[tokio::test]
+async fn unknown_project_names_fall_back_without_failing() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    Mock::given(method("GET"))
+        .and(path("/pages/project1id"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "object": "error",
+            "status": 404,
+            "code": "object_not_found",
+            "message": "Could not find page"
+        })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["generate-stand-up"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let stand_up = stdout(&output);
+    assert!(stand_up.contains("Could not get the name of project project1id"));
+    assert!(stand_up.contains("Today\n • Fix login\n • WIP Review PR\nTomorrow"));
+}
+
+#[tokio::test]
+async fn past_date_bounds_the_query_and_the_header() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+
+    let output = env.run(&["generate-stand-up", "--date", "2026-10-12"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Stand-up Oct 12th\nToday"));
+
+    let requests = env.requests_to("/databases/task-db/query").await;
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["filter"]["and"][1]["last_edited_time"], json!({ "on_or_after": "2026-10-12" }));
+    assert_eq!(body["filter"]["and"][2]["last_edited_time"], json!({ "on_or_before": "2026-10-12" }));
+}
+
+#[tokio::test]
+async fn previous_working_day_skips_weekends_and_holidays() {
+    let mut env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    env.env("STANDUP_HOLIDAYS", "2026-10-09, 2026-12-25");
+
+    let output = env
+        .run(&["generate-stand-up", "--date", "2026-10-12", "--previous-working-day"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let requests = env.requests_to("/databases/task-db/query").await;
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["filter"]["and"][1]["last_edited_time"], json!({ "on_or_after": "2026-10-08" }));
+}
+
+#[tokio::test]
+async fn since_after_the_stand_up_date_is_rejected() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+
+    let output = env
+        .run(&["generate-stand-up", "--date", "2026-10-12", "--since", "2026-10-13"])
+        .await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("--since 2026-10-13 is after the stand-up date 2026-10-12"));
+    assert!(env.requests_to("/databases/task-db/query").await.is_empty());
+}
+
+#[tokio::test]
+async fn future_date_is_rejected() {
+    let env = TestEnv::new().await;
+    mock_standup_tasks(&env).await;
+    mock_timelog_sheet(&env, &[]).await;
+    let tomorrow = (Local::now().date_naive() + chrono::Duration::days(1)).to_string();
+
+    let output = env.run(&["generate-stand-up", "--date", &tomorrow, "--timelog"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains(&format!("--date {} is in the future", tomorrow)));
+    assert!(env.requests_to("/databases/task-db/query").await.is_empty());
+    assert!(timelog_writes(&env).await.is_empty());
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::{stdout, TestEnv};
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+fn article(id: &str, read: bool) -> Value {
+    json!({
+        "object": "page",
+        "id": id,
+        "url": format!("https://notion.so/{}", id),
+        "properties": { "Did I read it": { "checkbox": read } }
+    })
+}
+
+#[tokio::test]
+async fn writes_the_queried_articles_to_reading_list() {
+    let mut env = TestEnv::new().await;
+    let csv = env.write_file("reading_list.csv", "id,url,did_i_read_it,priority\n");
+    env.env("READING_LIST_CSV", &csv);
+
+    Mock::given(method("POST"))
+        .and(path("/databases/reading-db/query"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "results": [article("first", false), article("second", true)],
+            "has_more": false,
+            "next_cursor": null
+        })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["give-me-article", "--no-open"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("https://notion.so/"));
+
+    let requests = env.requests_to("/databases/reading-db/query").await;
+    assert_eq!(requests.len(), 1);
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["page_size"], 100);
+    assert_eq!(requests[0].headers["authorization"], "Bearer notion-key");
+
+    let reading_list = std::fs::read_to_string(&csv).unwrap();
+    assert!(reading_list.contains("first,https://notion.so/first,false"));
+    assert!(reading_list.contains("second,https://notion.so/second,true"));
+}
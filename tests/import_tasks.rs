@@ -0,0 +1,101 @@
+mod common;
+
+use common::{stdout, TestEnv};
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+async fn env_accepting_pages() -> TestEnv {
+    let env = TestEnv::new().await;
+    env.write_project_cache(&[("Automate Me", "automate-id"), ("Blog", "blog-id")]);
+    Mock::given(method("POST"))
+        .and(path("/pages"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "page",
+            "id": "new-page",
+            "url": "https://www.notion.so/new-page"
+        })))
+        .mount(&env.server)
+        .await;
+    env
+}
+
+async fn sent_tasks(env: &TestEnv) -> Vec<(String, String, String)> {
+    let mut tasks: Vec<(String, String, String)> = env
+        .requests_to("/pages")
+        .await
+        .iter()
+        .map(|request| {
+            let body: Value = request.body_json().unwrap();
+            let properties = &body["properties"];
+            (
+                properties["Name"]["title"][0]["plain_text"].as_str().unwrap().to_string(),
+                properties["Status"]["status"]["name"].as_str().unwrap().to_string(),
+                properties["Projects"]["relation"][0]["id"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    tasks.sort();
+    tasks
+}
+
+#[tokio::test]
+async fn imports_markdown_checklist_with_heading_projects() {
+    let env = env_accepting_pages().await;
+    let file = env.write_file(
+        "tasks.md",
+        "# Automate Me\n- [x] Paginate queries\n- [ ] Write docs\n\nnotes are ignored\n## Blog\n* [X] Publish post\n",
+    );
+
+    let output = env.run(&["add-task", "--from-file", &file]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Created 3 tasks, 0 failed"));
+    assert_eq!(
+        sent_tasks(&env).await,
+        vec![
+            ("Paginate queries".to_string(), "Done".to_string(), "automate-id".to_string()),
+            ("Publish post".to_string(), "Done".to_string(), "blog-id".to_string()),
+            ("Write docs".to_string(), "To Do".to_string(), "automate-id".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn imports_csv_from_stdin_and_reports_failed_rows() {
+    let env = env_accepting_pages().await;
+    let csv = "task,status,project\nFix login,in progress,blog\nPlan sprint,,\nRefactor,done,unknown\n";
+
+    let output = env
+        .run_with_stdin(&["add-task", "--from-file", "-", "-p", "automate"], csv)
+        .await;
+    assert_eq!(output.status.code(), Some(3));
+    let summary = stdout(&output);
+    assert!(summary.contains("Created 2 tasks, 1 failed"));
+    assert!(summary.contains("line 4 Refactor"));
+    assert_eq!(
+        sent_tasks(&env).await,
+        vec![
+            ("Fix login".to_string(), "In progress".to_string(), "blog-id".to_string()),
+            ("Plan sprint".to_string(), "Done".to_string(), "automate-id".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn bad_csv_row_is_reported_on_its_own_line() {
+    let env = env_accepting_pages().await;
+    let file = env.write_file("tasks.csv", "task,status,project\n\"Write\ndocs\",done,blog\n,done,blog\nShip,done,blog\n");
+
+    let output = env.run(&["add-task", "--from-file", &file]).await;
+    assert_eq!(output.status.code(), Some(3));
+    let summary = stdout(&output);
+    assert!(summary.contains("Created 2 tasks, 1 failed"));
+    assert!(summary.contains("line 4 : the task column is empty"));
+    assert_eq!(
+        sent_tasks(&env).await,
+        vec![
+            ("Ship".to_string(), "Done".to_string(), "blog-id".to_string()),
+            ("Write\ndocs".to_string(), "Done".to_string(), "blog-id".to_string()),
+        ]
+    );
+}
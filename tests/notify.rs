@@ -0,0 +1,127 @@
+mod common;
+
+use common::{mock_tasks, stderr, stdout, task, TestEnv};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+// Accepts one SMTP session and hands back everything the client wrote
+async fn fake_smtp_server() -> (u16, tokio::task::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let session = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut transcript = String::new();
+        let mut in_data = false;
+        writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            transcript.push_str(&line);
+            transcript.push('\n');
+            let reply: &[u8] = if in_data {
+                if line != "." {
+                    continue;
+                }
+                in_data = false;
+                b"250 queued\r\n"
+            } else if line.starts_with("DATA") {
+                in_data = true;
+                b"354 go ahead\r\n"
+            } else if line.starts_with("QUIT") {
+                writer.write_all(b"221 bye\r\n").await.unwrap();
+                break;
+            } else {
+                b"250 OK\r\n"
+            };
+            writer.write_all(reply).await.unwrap();
+        }
+        transcript
+    });
+    (port, session)
+}
+
+#[tokio::test]
+async fn fans_out_to_every_configured_target() {
+    let mut env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    for endpoint in ["/discord-webhook", "/teams-webhook"] {
+        Mock::given(method("POST"))
+            .and(path(endpoint))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&env.server)
+            .await;
+    }
+    Mock::given(method("POST"))
+        .and(path("/chat.postMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true, "channel": "C123", "ts": "1.2" })))
+        .mount(&env.server)
+        .await;
+    let (port, smtp) = fake_smtp_server().await;
+    let discord = format!("{}/discord-webhook", env.server.uri());
+    let teams = format!("{}/teams-webhook", env.server.uri());
+    env.env("NOTIFY_TARGETS", "slack, discord, teams, email")
+        .env("DISCORD_WEBHOOK_URL", &discord)
+        .env("TEAMS_WEBHOOK_URL", &teams)
+        .env("SMTP_HOST", "127.0.0.1")
+        .env("SMTP_PORT", &port.to_string())
+        .env("EMAIL_FROM", "me@example.com")
+        .env("EMAIL_TO", "team@example.com, lead@example.com");
+
+    let output = env.run(&["generate-stand-up", "--notify"]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    assert_eq!(env.requests_to("/chat.postMessage").await.len(), 1);
+    let discord: Value = env.requests_to("/discord-webhook").await[0].body_json().unwrap();
+    assert!(discord["content"].as_str().unwrap().contains("Today\n • Fix login"));
+    let teams: Value = env.requests_to("/teams-webhook").await[0].body_json().unwrap();
+    assert_eq!(teams["@type"], "MessageCard");
+    assert!(teams["title"].as_str().unwrap().starts_with("Stand-up "));
+    assert!(teams["text"].as_str().unwrap().contains("Today\n\n • Fix login"));
+
+    let transcript = smtp.await.unwrap();
+    assert!(transcript.contains("MAIL FROM:<me@example.com>"));
+    assert!(transcript.contains("RCPT TO:<team@example.com>"));
+    assert!(transcript.contains("RCPT TO:<lead@example.com>"));
+    assert!(transcript.contains("Subject: Stand-up "));
+}
+
+#[tokio::test]
+async fn one_failing_target_is_a_partial_success() {
+    let mut env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    Mock::given(method("POST"))
+        .and(path("/discord-webhook"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Unknown Webhook"))
+        .mount(&env.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/teams-webhook"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&env.server)
+        .await;
+    let discord = format!("{}/discord-webhook", env.server.uri());
+    let teams = format!("{}/teams-webhook", env.server.uri());
+    env.env("NOTIFY_TARGETS", "discord,teams")
+        .env("DISCORD_WEBHOOK_URL", &discord)
+        .env("TEAMS_WEBHOOK_URL", &teams);
+
+    let output = env.run(&["generate-stand-up", "-n"]).await;
+    assert_eq!(output.status.code(), Some(8));
+    assert!(stdout(&output).contains("Partial success: Teams succeeded but Discord failed"));
+    assert!(stderr(&output).contains("Sending the stand-up to Discord failed: 404 Not Found Unknown Webhook"));
+}
+
+#[tokio::test]
+async fn missing_target_settings_fail_before_querying() {
+    let mut env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    env.env("NOTIFY_TARGETS", "teams, pager");
+
+    let output = env.run(&["generate-stand-up", "--notify"]).await;
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("unknown target `pager`"));
+    assert!(env.requests_to("/databases/task-db/query").await.is_empty());
+}
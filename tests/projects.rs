@@ -0,0 +1,116 @@
+mod common;
+
+use common::{stderr, stdout, TestEnv};
+use serde_json::{json, Value};
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+fn project(id: &str, name: &str) -> Value {
+    json!({
+        "object": "page",
+        "id": id,
+        "properties": {
+            "Status": { "type": "select", "select": null },
+            "Project name": { "type": "title", "title": [{ "plain_text": name }] }
+        }
+    })
+}
+
+#[tokio::test]
+async fn sync_writes_every_project_to_the_cache() {
+    let env = TestEnv::new().await;
+    Mock::given(method("POST"))
+        .and(path("/databases/projects-db/query"))
+        .and(body_partial_json(json!({ "start_cursor": "next" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "results": [project("blog-id", "Blog")],
+            "has_more": false,
+            "next_cursor": null
+        })))
+        .with_priority(1)
+        .mount(&env.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/databases/projects-db/query"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "results": [project("automate-id", "Automate Me")],
+            "has_more": true,
+            "next_cursor": "next"
+        })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["projects", "sync"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Synced 2 projects"));
+
+    let cache = std::fs::read_to_string(env.path().join("cache/automate-me/projects.json")).unwrap();
+    let cache: Value = serde_json::from_str(&cache).unwrap();
+    assert_eq!(
+        cache["projects"],
+        json!([{ "id": "automate-id", "name": "Automate Me" }, { "id": "blog-id", "name": "Blog" }])
+    );
+
+    let output = env.run(&["projects", "list"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Automate Me"));
+    assert!(stdout(&output).contains("blog-id"));
+}
+
+#[tokio::test]
+async fn list_without_cache_asks_for_sync() {
+    let env = TestEnv::new().await;
+
+    let output = env.run(&["projects", "list"]).await;
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr(&output).contains("run `automate-me projects sync` first"));
+}
+
+async fn add_task_to(env: &TestEnv, project: &str) -> std::process::Output {
+    Mock::given(method("POST"))
+        .and(path("/pages"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "page",
+            "id": "new-page",
+            "url": "https://www.notion.so/new-page"
+        })))
+        .mount(&env.server)
+        .await;
+    env.run(&["add-task", "-t", "Write tests", "-p", project]).await
+}
+
+#[tokio::test]
+async fn add_task_resolves_unique_prefix() {
+    let env = TestEnv::new().await;
+    env.write_project_cache(&[("Automate Me", "automate-id"), ("Blog", "blog-id")]);
+
+    let output = add_task_to(&env, "auto").await;
+    assert!(output.status.success(), "{:?}", output);
+    let requests = env.requests_to("/pages").await;
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["properties"]["Projects"]["relation"], json!([{ "id": "automate-id" }]));
+}
+
+#[tokio::test]
+async fn add_task_suggests_close_names() {
+    let env = TestEnv::new().await;
+    env.write_project_cache(&[("Automate Me", "automate-id"), ("Blog", "blog-id")]);
+
+    let output = add_task_to(&env, "blgo").await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("Unknown project `blgo`, did you mean: Blog"));
+    assert!(env.requests_to("/pages").await.is_empty());
+}
+
+#[tokio::test]
+async fn add_task_rejects_ambiguous_prefix() {
+    let env = TestEnv::new().await;
+    env.write_project_cache(&[("Blog", "blog-id"), ("Blog Redesign", "redesign-id")]);
+
+    let output = add_task_to(&env, "blog r").await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = add_task_to(&env, "bl").await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("ambiguous, did you mean: Blog, Blog Redesign"));
+}
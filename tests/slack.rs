@@ -0,0 +1,221 @@
+mod common;
+
+use chrono::prelude::*;
+use common::{mock_tasks, mock_timelog_sheet, stderr, stdout, task, timelog_writes, TestEnv};
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path, path_regex};
+use wiremock::{Mock, ResponseTemplate};
+
+async fn mock_tasks_and_slack(env: &TestEnv) {
+    mock_tasks(env, vec![task("Fix login", "Done")]).await;
+    for endpoint in ["/chat.postMessage", "/chat.update"] {
+        Mock::given(method("POST"))
+            .and(path(endpoint))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "ok": true,
+                "channel": "C123",
+                "ts": "1760000000.000100"
+            })))
+            .mount(&env.server)
+            .await;
+    }
+}
+
+#[tokio::test]
+async fn replies_in_thread_and_update_edits_the_same_message() {
+    let env = TestEnv::new().await;
+    mock_tasks_and_slack(&env).await;
+
+    let output = env
+        .run(&["generate-stand-up", "--slack", "--thread-ts", "1759990000.000200"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    let posted: Value = env.requests_to("/chat.postMessage").await[0].body_json().unwrap();
+    assert_eq!(posted["thread_ts"], "1759990000.000200");
+
+    let output = env.run(&["generate-stand-up", "--slack", "--update"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(env.requests_to("/chat.postMessage").await.len(), 1);
+    let updated: Value = env.requests_to("/chat.update").await[0].body_json().unwrap();
+    assert_eq!(updated["channel"], "C123");
+    assert_eq!(updated["ts"], "1760000000.000100");
+    assert!(updated["text"].as_str().unwrap().contains("Fix login"));
+}
+
+#[tokio::test]
+async fn finds_todays_thread_by_pattern() {
+    let mut env = TestEnv::new().await;
+    mock_tasks_and_slack(&env).await;
+    env.env("SLACK_THREAD_PATTERN", "Daily stand-up thread");
+    Mock::given(method("GET"))
+        .and(path("/conversations.history"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "ok": true,
+            "messages": [
+                { "ts": "1760000300.000000", "text": "lunch?" },
+                { "ts": "1760000200.000000", "text": "Daily stand-up thread :wave:" }
+            ]
+        })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["generate-stand-up", "--slack"]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let history = env.requests_to("/conversations.history").await;
+    assert!(history[0].url.query_pairs().any(|(key, value)| key == "channel" && value == "standup"));
+    assert!(history[0].url.query_pairs().any(|(key, _)| key == "oldest"));
+    let posted: Value = env.requests_to("/chat.postMessage").await[0].body_json().unwrap();
+    assert_eq!(posted["thread_ts"], "1760000200.000000");
+}
+
+#[tokio::test]
+async fn update_without_a_previous_post_is_rejected() {
+    let env = TestEnv::new().await;
+    mock_tasks_and_slack(&env).await;
+
+    let output = env.run(&["generate-stand-up", "--slack", "--update"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("run without --update"));
+    assert!(env.requests_to("/chat.update").await.is_empty());
+}
+
+#[tokio::test]
+async fn slack_error_fails_with_hint() {
+    let env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    Mock::given(method("POST"))
+        .and(path("/chat.postMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": false, "error": "not_in_channel" })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["generate-stand-up", "--slack"]).await;
+    assert_eq!(output.status.code(), Some(6));
+    let error = stderr(&output);
+    assert!(error.contains("Slack chat.postMessage returned `not_in_channel`"));
+    assert!(error.contains("Invite yourself to slack.channel"));
+}
+
+#[tokio::test]
+async fn rate_limited_post_is_retried_after_the_given_delay() {
+    let env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    Mock::given(method("POST"))
+        .and(path("/chat.postMessage"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", "0")
+                .set_body_json(json!({ "ok": false, "error": "ratelimited" })),
+        )
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&env.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat.postMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true, "channel": "C123", "ts": "1.2" })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["generate-stand-up", "--slack"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(env.requests_to("/chat.postMessage").await.len(), 2);
+}
+
+#[tokio::test]
+async fn timelog_is_still_filled_when_slack_fails() {
+    let env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    Mock::given(method("POST"))
+        .and(path("/chat.postMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": false, "error": "invalid_auth" })))
+        .mount(&env.server)
+        .await;
+    mock_timelog_sheet(&env, &[]).await;
+
+    let output = env.run(&["generate-stand-up", "--slack", "--timelog"]).await;
+    assert_eq!(output.status.code(), Some(6));
+    assert!(stdout(&output).contains("Partial success: timelog succeeded but Slack failed"));
+    assert!(stderr(&output).contains("Check slack.user_token"));
+    let puts = timelog_writes(&env).await.len();
+    assert_eq!(puts, 1);
+}
+
+#[tokio::test]
+async fn slack_is_still_posted_when_the_timelog_write_fails() {
+    let env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    Mock::given(method("POST"))
+        .and(path("/chat.postMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true, "channel": "C123", "ts": "1.2" })))
+        .mount(&env.server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path_regex("^/v4/spreadsheets/sheet-id/values/"))
+        .respond_with(ResponseTemplate::new(500))
+        .with_priority(1)
+        .mount(&env.server)
+        .await;
+    mock_timelog_sheet(&env, &[]).await;
+
+    let output = env.run(&["generate-stand-up", "--slack", "--timelog"]).await;
+    assert_eq!(output.status.code(), Some(7));
+    assert!(stdout(&output).contains("Partial success: Slack succeeded but timelog failed"));
+    assert_eq!(env.requests_to("/chat.postMessage").await.len(), 1);
+}
+
+#[tokio::test]
+async fn scheduled_stand_up_can_be_listed_and_cancelled() {
+    let env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    Mock::given(method("POST"))
+        .and(path("/chat.scheduleMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "ok": true,
+            "channel": "C123",
+            "scheduled_message_id": "Q1298393284",
+            "post_at": 4070908800i64
+        })))
+        .mount(&env.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat.deleteScheduledMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+        .mount(&env.server)
+        .await;
+
+    let output = env
+        .run(&["generate-stand-up", "--slack", "--at", "2099-01-01 09:30"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Scheduled for Thu 2099-01-01 09:30"));
+    assert!(env.requests_to("/chat.postMessage").await.is_empty());
+    let scheduled: Value = env.requests_to("/chat.scheduleMessage").await[0].body_json().unwrap();
+    assert_eq!(scheduled["channel"], "standup");
+    assert!(scheduled["post_at"].as_i64().unwrap() > Local::now().timestamp());
+    assert!(scheduled["blocks"].is_array());
+
+    let output = env.run(&["slack", "scheduled", "list"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Q1298393284"));
+
+    let output = env.run(&["slack", "scheduled", "cancel", "Q1298393284"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let deleted: Value = env.requests_to("/chat.deleteScheduledMessage").await[0].body_json().unwrap();
+    assert_eq!(deleted, json!({ "channel": "C123", "scheduled_message_id": "Q1298393284" }));
+
+    let output = env.run(&["slack", "scheduled", "list"]).await;
+    assert!(stdout(&output).contains("No scheduled stand-ups"));
+}
+
+#[tokio::test]
+async fn invalid_at_time_is_rejected_before_querying() {
+    let env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+
+    let output = env.run(&["generate-stand-up", "--slack", "--at", "25:00"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("use HH:MM or YYYY-MM-DD HH:MM"));
+    assert!(env.requests_to("/databases/task-db/query").await.is_empty());
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use common::{mock_tasks, stderr, stdout, TestEnv};
+use serde_json::{json, Value};
+use wiremock::matchers::method;
+use wiremock::{Mock, ResponseTemplate};
+
+fn task(id: &str, name: &str, status: &str) -> Value {
+    json!({
+        "object": "page",
+        "id": id,
+        "last_edited_time": "2026-10-01T09:00:00.000Z",
+        "properties": {
+            "Name": { "title": [{ "plain_text": name, "text": { "content": name } }] },
+            "Status": { "status": { "name": status } },
+            "Projects": { "type": "relation", "relation": [{ "id": "blog-id" }], "has_more": false }
+        }
+    })
+}
+
+async fn env_with_tasks() -> TestEnv {
+    let env = TestEnv::new().await;
+    env.write_project_cache(&[("Blog", "blog-id")]);
+    mock_tasks(
+        &env,
+        vec![
+            task("1a2b3c4d-0000-4000-8000-000000000001", "Write blog post", "To Do"),
+            task("9f8e7d6c-0000-4000-8000-000000000002", "Review blog comments", "In progress"),
+            task("5e5e5e5e-0000-4000-8000-000000000003", "Fix login", "To Do"),
+            task("9f8e1111-0000-4000-8000-000000000004", "Draft newsletter", "To Do"),
+            task("5e5e7777-0000-4000-8000-000000000005", "Old blog draft", "Archived"),
+        ],
+    )
+    .await;
+    Mock::given(method("PATCH"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "page",
+            "url": "https://www.notion.so/updated"
+        })))
+        .mount(&env.server)
+        .await;
+    env
+}
+
+async fn patches(env: &TestEnv) -> Vec<(String, Value)> {
+    env.server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|request| request.method.as_str() == "PATCH")
+        .map(|request| (request.url.path().to_string(), request.body_json().unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn list_filters_by_status_project_and_date() {
+    let env = env_with_tasks().await;
+
+    let output = env
+        .run(&["tasks", "list", "-s", "to do", "-p", "blog", "--since", "2026-09-01"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    let listed = stdout(&output);
+    assert!(listed.contains("1a2b3c4d"));
+    assert!(listed.contains("Write blog post"));
+    assert!(listed.contains("Blog"));
+
+    let requests = env.requests_to("/databases/task-db/query").await;
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(
+        body["filter"]["and"],
+        json!([
+            { "property": "Last edited time", "last_edited_time": { "on_or_after": "2026-09-01" } },
+            { "property": "Status", "status": { "equals": "To Do" } },
+            { "property": "Projects", "relation": { "contains": "blog-id" } }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn list_blocked_uses_the_configured_status() {
+    let mut env = env_with_tasks().await;
+    env.env("STANDUP_BLOCKED_STATUS", "Archived");
+
+    let output = env.run(&["tasks", "list", "-s", "blocked"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Old blog draft"));
+
+    let body: Value = env.requests_to("/databases/task-db/query").await[0].body_json().unwrap();
+    assert_eq!(body["filter"]["and"][1], json!({ "property": "Status", "status": { "equals": "Archived" } }));
+}
+
+#[tokio::test]
+async fn start_patches_status_by_short_id() {
+    let env = env_with_tasks().await;
+
+    let output = env.run(&["tasks", "start", "5e5e5e5e"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Updated Fix login"));
+    assert_eq!(
+        patches(&env).await,
+        vec![(
+            "/pages/5e5e5e5e-0000-4000-8000-000000000003".to_string(),
+            json!({ "properties": { "Status": { "status": { "name": "In progress" } } } })
+        )]
+    );
+}
+
+#[tokio::test]
+async fn done_finds_task_by_name() {
+    let env = env_with_tasks().await;
+
+    let output = env.run(&["tasks", "done", "review"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let patches = patches(&env).await;
+    assert_eq!(patches[0].0, "/pages/9f8e7d6c-0000-4000-8000-000000000002");
+    assert_eq!(patches[0].1["properties"]["Status"]["status"]["name"], "Done");
+}
+
+#[tokio::test]
+async fn rename_sets_title() {
+    let env = env_with_tasks().await;
+
+    let output = env.run(&["tasks", "rename", "1a2b", "Write release post"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let patches = patches(&env).await;
+    assert_eq!(
+        patches[0].1,
+        json!({ "properties": { "Name": { "title": [{
+            "plain_text": "Write release post",
+            "text": { "content": "Write release post" }
+        }] } } })
+    );
+}
+
+#[tokio::test]
+async fn ambiguous_name_lists_short_ids() {
+    let env = env_with_tasks().await;
+
+    let output = env.run(&["tasks", "done", "blog"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("1a2b3c4d  Write blog post"));
+    assert!(stderr(&output).contains("9f8e7d6c  Review blog comments"));
+    assert!(!stderr(&output).contains("Old blog draft"));
+    assert!(patches(&env).await.is_empty());
+}
+
+#[tokio::test]
+async fn ambiguous_id_prefix_is_reported() {
+    let env = env_with_tasks().await;
+
+    let output = env.run(&["tasks", "start", "9f8e"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("`9f8e` matches several tasks, use more of the page id"));
+    assert!(stderr(&output).contains("9f8e7d6c-0000-4000-8000-000000000002  Review blog comments"));
+    assert!(stderr(&output).contains("9f8e1111-0000-4000-8000-000000000004  Draft newsletter"));
+    assert!(patches(&env).await.is_empty());
+}
+
+#[tokio::test]
+async fn full_page_id_is_patched_without_a_lookup() {
+    let env = env_with_tasks().await;
+
+    let output = env.run(&["tasks", "done", "0123456789abcdef0123456789abcdef"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(env.requests_to("/databases/task-db/query").await.is_empty());
+    let patches = patches(&env).await;
+    assert_eq!(patches[0].0, "/pages/0123456789abcdef0123456789abcdef");
+    assert_eq!(patches[0].1["properties"]["Status"]["status"]["name"], "Done");
+}
+
+#[tokio::test]
+async fn list_leaves_out_tasks_with_other_statuses() {
+    let env = env_with_tasks().await;
+
+    let output = env.run(&["tasks", "list"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    let listed = stdout(&output);
+    assert!(listed.contains("Draft newsletter"));
+    assert!(!listed.contains("Old blog draft"));
+}
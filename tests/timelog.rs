@@ -0,0 +1,379 @@
+mod common;
+
+use chrono::prelude::*;
+use common::{
+    mock_tasks, mock_timelog_sheet, mock_timelog_values, stderr, stdout, task, timelog_sheet_name, timelog_writes, TestEnv,
+    TIMELOG_HEADER,
+};
+use serde_json::{json, Value};
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+async fn mock_batch_update(env: &TestEnv) {
+    Mock::given(method("POST"))
+        .and(path("/v4/spreadsheets/sheet-id:batchUpdate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "spreadsheetId": "sheet-id",
+            "replies": [{ "addSheet": { "properties": { "sheetId": 9, "title": "new" } } }]
+        })))
+        .mount(&env.server)
+        .await;
+}
+
+async fn batch_updates(env: &TestEnv) -> Vec<Value> {
+    env.requests_to("/v4/spreadsheets/sheet-id:batchUpdate")
+        .await
+        .iter()
+        .map(|request| request.body_json().unwrap())
+        .collect()
+}
+
+const HOLIDAY: [f64; 3] = [0.6, 0.8, 1.0];
+const LEAVE: [f64; 3] = [0.7, 0.9, 0.6];
+
+// Background of a cell, rounded as colours are 32 bit floats
+fn background(cell: &Value) -> [f64; 3] {
+    let color = &cell["userEnteredFormat"]["backgroundColor"];
+    ["red", "green", "blue"].map(|channel| (color[channel].as_f64().unwrap() * 100.0).round() / 100.0)
+}
+
+// Two days of this month that aren't today
+fn other_days() -> (NaiveDate, NaiveDate) {
+    let today = Local::now().date_naive();
+    let mut days = (1..=3).filter(|day| *day != today.day()).map(|day| today.with_day(day).unwrap());
+    (days.next().unwrap(), days.next().unwrap())
+}
+
+#[tokio::test]
+async fn leave_is_recorded_and_marked_on_the_existing_row() {
+    let env = TestEnv::new().await;
+    mock_timelog_sheet(&env, &[]).await;
+    mock_batch_update(&env).await;
+    let today = Local::now().date_naive();
+
+    let date = today.format("%Y-%m-%d").to_string();
+    let output = env.run(&["timelog", "leave", &date, "--reason", "Dentist"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains(&format!("Marked {} as Leave: Dentist in {}", date, timelog_sheet_name())));
+
+    let leave = std::fs::read_to_string(env.path().join("data/automate-me/leave.json")).unwrap();
+    let leave: Value = serde_json::from_str(&leave).unwrap();
+    assert_eq!(leave["days"][&date], "Dentist");
+
+    let body = &batch_updates(&env).await[0];
+    let format = &body["requests"][0]["updateCells"];
+    assert_eq!(format["fields"], "userEnteredFormat");
+    assert_eq!(format["range"]["startRowIndex"], today.day());
+    assert_eq!(format["range"]["endColumnIndex"], 4);
+    assert_eq!(background(&format["rows"][0]["values"][3]), LEAVE);
+    let label = &body["requests"][1]["updateCells"];
+    assert_eq!(label["fields"], "userEnteredValue");
+    assert_eq!(label["range"]["startColumnIndex"], 2);
+    assert_eq!(label["rows"][0]["values"][0]["userEnteredValue"]["stringValue"], "Leave: Dentist");
+}
+
+#[tokio::test]
+async fn leave_keeps_what_was_logged_that_day() {
+    let env = TestEnv::new().await;
+    mock_timelog_sheet(&env, &["Office", "Half a day of work", "4"]).await;
+    mock_batch_update(&env).await;
+
+    let date = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let output = env.run(&["timelog", "leave", &date]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let body = &batch_updates(&env).await[0];
+    assert_eq!(body["requests"].as_array().unwrap().len(), 1);
+    assert_eq!(body["requests"][0]["updateCells"]["fields"], "userEnteredFormat");
+}
+
+#[tokio::test]
+async fn leave_waits_for_a_sheet_that_does_not_exist_yet() {
+    let env = TestEnv::new().await;
+    Mock::given(method("GET"))
+        .and(path("/v4/spreadsheets/sheet-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "spreadsheetId": "sheet-id", "sheets": [] })))
+        .mount(&env.server)
+        .await;
+
+    let output = env.run(&["timelog", "leave", "2099-01-05"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Jan(2099) doesn't exist yet, 2099-01-05 is marked when it is created"));
+    assert!(batch_updates(&env).await.is_empty());
+}
+
+#[tokio::test]
+async fn new_sheet_labels_holidays_and_leave_in_their_own_colour() {
+    let mut env = TestEnv::new().await;
+    let (holiday, leave) = other_days();
+    let calendar = env.write_file(
+        "holidays.ics",
+        &format!(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:{}\r\nSUMMARY:Founders\\, Day\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+            holiday.format("%Y%m%d")
+        ),
+    );
+    env.env("HOLIDAY_CALENDAR", &calendar);
+    let leave_file = env.write_file(
+        "leave.json",
+        &json!({ "days": { leave.format("%Y-%m-%d").to_string(): null } }).to_string(),
+    );
+    env.env("LEAVE_JSON", &leave_file);
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    Mock::given(method("GET"))
+        .and(path("/v4/spreadsheets/sheet-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "spreadsheetId": "sheet-id", "sheets": [] })))
+        .mount(&env.server)
+        .await;
+    mock_batch_update(&env).await;
+
+    let output = env.run(&["generate-stand-up", "--timelog"]).await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let body = &batch_updates(&env).await[1];
+    let rows = &body["requests"][1]["updateCells"]["rows"];
+    let holiday_row = &rows[holiday.day0() as usize]["values"];
+    assert_eq!(holiday_row[2]["userEnteredValue"]["stringValue"], "Founders, Day");
+    assert_eq!(background(&holiday_row[0]), HOLIDAY);
+    let leave_row = &rows[leave.day0() as usize]["values"];
+    assert_eq!(leave_row[2]["userEnteredValue"]["stringValue"], "Leave");
+    assert_eq!(background(&leave_row[1]), LEAVE);
+}
+
+#[tokio::test]
+async fn holiday_list_adds_to_the_calendar_and_recurring_events_are_warned_about() {
+    let mut env = TestEnv::new().await;
+    let (holiday, extra) = other_days();
+    let calendar = env.write_file(
+        "holidays.ics",
+        &format!(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:{}\r\nRRULE:FREQ=YEARLY\r\nSUMMARY:Founders Day\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+            holiday.format("%Y%m%d")
+        ),
+    );
+    env.env("HOLIDAY_CALENDAR", &calendar).env(
+        "STANDUP_HOLIDAYS",
+        &format!("{}, {}", holiday.format("%Y-%m-%d"), extra.format("%Y-%m-%d")),
+    );
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    Mock::given(method("GET"))
+        .and(path("/v4/spreadsheets/sheet-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "spreadsheetId": "sheet-id", "sheets": [] })))
+        .mount(&env.server)
+        .await;
+    mock_batch_update(&env).await;
+
+    let output = env.run(&["generate-stand-up", "--timelog"]).await;
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(stderr(&output).matches("recurring events (RRULE) only count on their first day").count(), 1);
+    assert!(stderr(&output).contains(": Founders Day"));
+
+    let body = &batch_updates(&env).await[1];
+    let rows = &body["requests"][1]["updateCells"]["rows"];
+    assert_eq!(rows[holiday.day0() as usize]["values"][2]["userEnteredValue"]["stringValue"], "Founders Day");
+    assert_eq!(rows[extra.day0() as usize]["values"][2]["userEnteredValue"]["stringValue"], "Holiday");
+}
+
+#[tokio::test]
+async fn yaml_holidays_and_weekend_decide_the_previous_working_day() {
+    let mut env = TestEnv::new().await;
+    let holidays = env.write_file("holidays.yaml", "- date: 2026-10-15\n  label: Dashain\n");
+    env.env("HOLIDAY_CALENDAR", &holidays).env("STANDUP_WEEKEND", "Fri, Sat");
+    mock_tasks(&env, vec![]).await;
+
+    let output = env
+        .run(&["generate-stand-up", "--date", "2026-10-18", "--previous-working-day"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+
+    let requests = env.requests_to("/databases/task-db/query").await;
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(body["filter"]["and"][1]["last_edited_time"], json!({ "on_or_after": "2026-10-14" }));
+}
+
+#[tokio::test]
+async fn backfill_fills_missing_working_days_and_reports_filled_ones() {
+    let env = TestEnv::new().await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    let sheet = json!([TIMELOG_HEADER, ["09/24/2026", "WFH", "Manual entry", "8"], ["09/25/2026"]]);
+    Mock::given(method("GET"))
+        .and(path("/v4/spreadsheets/sheet-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "spreadsheetId": "sheet-id",
+            "sheets": [{ "properties": { "sheetId": 7, "title": "Sep(2026)" } }]
+        })))
+        .with_priority(1)
+        .mount(&env.server)
+        .await;
+    mock_timelog_values(&env, sheet).await;
+
+    let output = env
+        .run(&["timelog", "backfill", "--from", "2026-09-24", "--to", "2026-09-28", "-w", "6"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    let report = stdout(&output);
+    assert!(report.contains("Filled 2 day(s): 2026-09-25, 2026-09-28"));
+    assert!(report.contains("Skipped 1 day(s) that already had content: 2026-09-24"));
+    assert!(report.contains("Skipped 2 day(s) off"));
+
+    let queries = env.requests_to("/databases/task-db/query").await;
+    assert_eq!(queries.len(), 3);
+    let body: Value = queries[2].body_json().unwrap();
+    assert_eq!(body["filter"]["and"][1]["last_edited_time"], json!({ "on_or_after": "2026-09-28" }));
+    assert_eq!(body["filter"]["and"][2]["last_edited_time"], json!({ "on_or_before": "2026-09-28" }));
+
+    let writes = timelog_writes(&env).await;
+    assert_eq!(writes.len(), 2);
+    let update: Value = writes[0].body_json().unwrap();
+    assert_eq!(update["range"], "Sep(2026)!B3:D3");
+    assert_eq!(update["values"], json!([["WFH", " • Fix login", "6"]]));
+    let appended: Value = writes[1].body_json().unwrap();
+    let row = &appended["requests"][0]["appendCells"]["rows"][0]["values"];
+    assert_eq!(row[0]["userEnteredValue"]["stringValue"], "09/28/2026");
+    // The project the three days share is looked up once
+    assert_eq!(env.requests_to("/pages/project1id").await.len(), 1);
+}
+
+#[tokio::test]
+async fn backfill_goes_on_past_a_day_notion_fails_on() {
+    let env = TestEnv::new().await;
+    Mock::given(method("POST"))
+        .and(path("/databases/task-db/query"))
+        .and(body_string_contains("2026-09-25"))
+        .respond_with(ResponseTemplate::new(500).set_body_json(json!({ "message": "Internal error" })))
+        .with_priority(1)
+        .mount(&env.server)
+        .await;
+    mock_tasks(&env, vec![task("Fix login", "Done")]).await;
+    let sheet = json!([TIMELOG_HEADER, ["09/24/2026"], ["09/25/2026"], ["09/28/2026"]]);
+    Mock::given(method("GET"))
+        .and(path("/v4/spreadsheets/sheet-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "spreadsheetId": "sheet-id",
+            "sheets": [{ "properties": { "sheetId": 7, "title": "Sep(2026)" } }]
+        })))
+        .with_priority(1)
+        .mount(&env.server)
+        .await;
+    mock_timelog_values(&env, sheet).await;
+
+    let output = env
+        .run(&["timelog", "backfill", "--from", "2026-09-24", "--to", "2026-09-28"])
+        .await;
+    assert_eq!(output.status.code(), Some(5));
+    assert!(stdout(&output).contains("Filled 2 day(s): 2026-09-24, 2026-09-28"));
+    assert!(stderr(&output).contains("could not get the tasks of 2026-09-25"));
+    assert_eq!(timelog_writes(&env).await.len(), 2);
+}
+
+#[tokio::test]
+async fn backfill_rejects_a_range_ending_in_the_future() {
+    let env = TestEnv::new().await;
+    let tomorrow = (Local::now().date_naive() + chrono::Duration::days(1)).to_string();
+
+    let output = env
+        .run(&["timelog", "backfill", "--from", "2026-09-01", "--to", &tomorrow])
+        .await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains(&format!("--to {} is in the future", tomorrow)));
+    assert!(env.server.received_requests().await.unwrap().is_empty());
+}
+
+// Sep(2026) with every weekday logged except the 14th and 15th, the 1st and 2nd from the office
+async fn mock_september(env: &TestEnv) {
+    Mock::given(method("GET"))
+        .and(path("/v4/spreadsheets/sheet-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "spreadsheetId": "sheet-id",
+            "sheets": [{ "properties": { "sheetId": 7, "title": "Sep(2026)" } }]
+        })))
+        .with_priority(1)
+        .mount(&env.server)
+        .await;
+    let mut rows = vec![json!(TIMELOG_HEADER)];
+    for date in NaiveDate::from_ymd_opt(2026, 9, 1).unwrap().iter_days().take(30) {
+        let day = date.format("%m/%d/%Y").to_string();
+        rows.push(match (date.weekday(), date.day()) {
+            (Weekday::Sat | Weekday::Sun, _) | (_, 14 | 15) => json!([day]),
+            (_, 1) => json!([day, "Office", " • Fix <login>\n • Review | merge", "7.5"]),
+            (_, 2) => json!([day, "office", "Release", "8"]),
+            _ => json!([day, "WFH", "Work", "8"]),
+        });
+    }
+    mock_timelog_values(env, json!(rows)).await;
+}
+
+#[tokio::test]
+async fn report_sums_the_month_and_exports_csv() {
+    let env = TestEnv::new().await;
+    mock_september(&env).await;
+
+    let output = env
+        .run(&["timelog", "report", "--month", "2026-09", "-o", "september.csv"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    let report = stdout(&output);
+    assert!(report.contains("Timelog of September 2026"));
+    assert!(report.contains("Hours:        159.50"));
+    assert!(report.contains("Office days:  2"));
+    assert!(report.contains("WFH days:     18"));
+    assert!(report.contains("Empty working days: 2026-09-14, 2026-09-15"));
+
+    let csv = std::fs::read_to_string(env.path().join("september.csv")).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "date,in_office,task,hours");
+    assert_eq!(lines[1], "2026-09-01,Office,\"• Fix <login>");
+    assert_eq!(lines[3], "2026-09-02,office,Release,8.00");
+    assert_eq!(*lines.last().unwrap(), "Total,,,159.50");
+    assert!(env.server.received_requests().await.unwrap().iter().all(|request| request.method.as_str() == "GET"));
+}
+
+#[tokio::test]
+async fn report_prints_markdown_with_amounts_for_a_rate() {
+    let env = TestEnv::new().await;
+    mock_september(&env).await;
+
+    let output = env
+        .run(&["timelog", "report", "--month", "2026-09", "--format", "markdown", "--rate", "50"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    let markdown = stdout(&output);
+    assert!(markdown.starts_with("# Timesheet September 2026\n"));
+    assert!(markdown.contains("| Date | In Office | Task | Hours | Amount |"));
+    assert!(markdown.contains("| 2026-09-01 | Office | • Fix <login><br> • Review \\| merge | 7.50 | 375.00 |"));
+    assert!(markdown.contains("| **Total** | | | **159.50** | **7975.00** |"));
+    assert!(markdown.contains("Empty working days: 2026-09-14, 2026-09-15"));
+    assert!(!markdown.contains("Timelog of"));
+}
+
+#[tokio::test]
+async fn report_exports_a_printable_html_invoice() {
+    let env = TestEnv::new().await;
+    mock_september(&env).await;
+
+    let output = env
+        .run(&["timelog", "report", "--month", "2026-09", "-o", "invoice.html", "-r", "40"])
+        .await;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Amount:       6380.00"));
+    let html = std::fs::read_to_string(env.path().join("invoice.html")).unwrap();
+    assert!(html.contains("@page"));
+    assert!(html.contains("<h1>Timesheet September 2026</h1>"));
+    assert!(html.contains("Fix &lt;login&gt;"));
+    assert!(html.contains("<td>Tue Sep 1</td>"));
+    assert!(html.contains("<td class=\"number\">159.50</td><td class=\"number\">6380.00</td>"));
+}
+
+#[tokio::test]
+async fn report_needs_the_month_sheet() {
+    let env = TestEnv::new().await;
+    mock_timelog_sheet(&env, &[]).await;
+
+    let output = env.run(&["timelog", "report", "--month", "2026-01"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("The timelog has no Jan(2026) sheet"));
+
+    let output = env.run(&["timelog", "report", "--month", "2026-09", "-o", "report.pdf"]).await;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("Can't tell the export format of report.pdf, pass --format"));
+}